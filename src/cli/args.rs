@@ -0,0 +1,405 @@
+use crate::core::config::AConfig;
+use crate::core::document::ADocFormat;
+use crate::core::export::{parse_color, AExportFormat, APageSize, ASvgOptions, BASE_DPI};
+
+// 命令行子命令
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ACommand {
+    // mermaid 转 ascii
+    Svgbob,
+    // ascii 转 svg
+    Svg,
+    // ascii 转 png
+    Png,
+    // ascii 转 pdf
+    Pdf,
+    // ascii 转包含 svg 的 html
+    Html,
+    // 格式化 asciidoc 表格
+    Table,
+    // ascii 图还原为 mermaid
+    Mermaid,
+    // 刷新 markdown/asciidoc 文档中 mermaid 块的渲染结果
+    Doc,
+    // 监视目录，文档修改后自动执行 doc
+    Watch,
+    // 基于 stdio 的语言服务
+    Lsp,
+    // 将流程图导出为 mermaid/dot/json/drawio/excalidraw
+    Export(AExportFormat),
+}
+
+impl ACommand {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "svgbob" => Some(ACommand::Svgbob),
+            "svg" => Some(ACommand::Svg),
+            "png" => Some(ACommand::Png),
+            "pdf" => Some(ACommand::Pdf),
+            "html" => Some(ACommand::Html),
+            "table" => Some(ACommand::Table),
+            "mermaid" => Some(ACommand::Mermaid),
+            "doc" => Some(ACommand::Doc),
+            "watch" => Some(ACommand::Watch),
+            "lsp" => Some(ACommand::Lsp),
+            // 格式由 --to 或者输出文件扩展名确定，解析选项之后替换
+            "export" => Some(ACommand::Export(AExportFormat::Mermaid)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AArgs {
+    pub command: ACommand,
+    // 为空或者 "-" 时读取标准输入，只有 doc 子命令允许多个文件
+    pub inputs: Vec<String>,
+    // 为空或者 "-" 时写入标准输出
+    pub output: Option<String>,
+    pub expand_mode: bool,
+    // svg/png/pdf/html 子命令的输入为 mermaid，先转换为 ascii
+    pub mermaid: bool,
+    // doc 子命令直接修改输入文件
+    pub in_place: bool,
+    // doc 子命令生成 svg 文件而不是 svgbob 块
+    pub svg_file: bool,
+    // doc 子命令只检查渲染结果是否过期，不写入文件
+    pub check: bool,
+    // doc 子命令的文档格式，为空时根据扩展名判断
+    pub format: Option<ADocFormat>,
+    // watch 子命令合并连续修改的等待时间，单位毫秒，为空时使用配置文件
+    pub debounce: Option<u64>,
+    // svg 样式，名称与配置文件 [svg] 中的字段相同，优先于配置文件
+    pub svg_set: Vec<(String, String)>,
+    // png 子命令的缩放比例，--dpi 换算为缩放比例，为空时使用配置文件
+    pub scale: Option<f32>,
+    // png 子命令的背景色，为空时使用配置文件
+    pub background: Option<String>,
+    // pdf 子命令的纸张大小，为空时使用配置文件
+    pub page: Option<APageSize>,
+    // pdf 子命令的页边距，单位毫米，为空时使用配置文件
+    pub margin: Option<f32>,
+    // html 子命令只输出 <figure> 片段
+    pub snippet: bool,
+    // svg 子命令输出逐步淡入的动画
+    pub animate: bool,
+    // svg 子命令每一步输出一个文件，文件名为 name-1.svg、name-2.svg ...
+    pub steps: bool,
+    // 指定配置文件，为空时从当前目录向上查找 asciibox.toml
+    pub config_path: Option<String>,
+    // 运行前加载的配置，命令行选项优先
+    pub config: AConfig,
+}
+
+// 只在启动时解析一次，不需要为了大小装箱
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AParsed {
+    Run(AArgs),
+    Help,
+    Version,
+}
+
+// 首个参数为子命令或帮助/版本选项时以命令行方式运行
+pub fn is_cli(args: &[String]) -> bool {
+    match args.first() {
+        Some(arg) => {
+            ACommand::from_name(arg).is_some()
+                || matches!(arg.as_str(), "help" | "-h" | "--help" | "-V" | "--version")
+        }
+        None => false,
+    }
+}
+
+// args 不包含程序名
+pub fn parse_args(args: &[String]) -> Result<AParsed, String> {
+    let name = match args.first() {
+        Some(v) => v.as_str(),
+        None => return Ok(AParsed::Help),
+    };
+    match name {
+        "help" | "-h" | "--help" => return Ok(AParsed::Help),
+        "-V" | "--version" => return Ok(AParsed::Version),
+        _ => {}
+    }
+    let command = match ACommand::from_name(name) {
+        Some(v) => v,
+        None => return Err(format!("unknown command '{}'", name)),
+    };
+    let mut parsed = AArgs {
+        command,
+        inputs: Vec::new(),
+        output: None,
+        expand_mode: false,
+        mermaid: false,
+        in_place: false,
+        svg_file: false,
+        check: false,
+        format: None,
+        debounce: None,
+        svg_set: Vec::new(),
+        scale: None,
+        background: None,
+        page: None,
+        margin: None,
+        snippet: false,
+        animate: false,
+        steps: false,
+        config_path: None,
+        config: AConfig::default(),
+    };
+    let is_doc = command == ACommand::Doc;
+    let is_watch = command == ACommand::Watch;
+    let is_lsp = command == ACommand::Lsp;
+    let is_export = matches!(command, ACommand::Export(_));
+    // export 子命令的输出格式，为空时根据输出文件扩展名判断
+    let mut export_format = None;
+    let is_png = command == ACommand::Png;
+    let is_pdf = command == ACommand::Pdf;
+    let is_html = command == ACommand::Html;
+    let has_svg = is_png || is_pdf || is_html || is_doc || is_watch || command == ACommand::Svg;
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(AParsed::Help),
+            "-o" | "--output" => match iter.next() {
+                Some(v) => parsed.output = Some(v.clone()),
+                None => return Err(format!("'{}' requires a file name", arg)),
+            },
+            "-c" | "--config" => match iter.next() {
+                Some(v) => parsed.config_path = Some(v.clone()),
+                None => return Err(format!("'{}' requires a file name", arg)),
+            },
+            "-e" | "--expand" if !matches!(command, ACommand::Table | ACommand::Mermaid) => {
+                parsed.expand_mode = true
+            }
+            "-m" | "--mermaid"
+                if matches!(
+                    command,
+                    ACommand::Svg | ACommand::Png | ACommand::Pdf | ACommand::Html
+                ) =>
+            {
+                parsed.mermaid = true
+            }
+            "-s" | "--set" if has_svg => {
+                let pair = iter.next().and_then(|v| v.split_once('='));
+                let (key, value) = match pair {
+                    Some((k, v)) => (k.trim().replace('-', "_"), v.trim().to_string()),
+                    None => return Err(format!("'{}' requires KEY=VALUE", arg)),
+                };
+                ASvgOptions::default().set(key.as_str(), value.as_str())?;
+                parsed.svg_set.push((key, value));
+            }
+            "--transparent" if has_svg => {
+                parsed
+                    .svg_set
+                    .push(("transparent".to_string(), "true".to_string()));
+            }
+            "--scale" | "--dpi" if is_png => {
+                let value = iter.next().and_then(|v| v.parse::<f32>().ok());
+                match value.filter(|v| *v > 0.0 && v.is_finite()) {
+                    Some(v) if arg == "--dpi" => parsed.scale = Some(v / BASE_DPI),
+                    Some(v) => parsed.scale = Some(v),
+                    None => return Err(format!("'{}' requires a positive number", arg)),
+                }
+            }
+            "--background" if is_png => match iter.next() {
+                Some(v) if parse_color(v).is_some() => parsed.background = Some(v.clone()),
+                _ => return Err(format!("'{}' requires a color such as #ffffff", arg)),
+            },
+            "-i" | "--in-place" if is_doc => parsed.in_place = true,
+            "--svg" if is_doc || is_watch => parsed.svg_file = true,
+            "--check" if is_doc => parsed.check = true,
+            "--debounce" if is_watch => match iter.next().and_then(|v| v.parse().ok()) {
+                Some(v) => parsed.debounce = Some(v),
+                None => return Err(format!("'{}' requires milliseconds", arg)),
+            },
+            "--page" if is_pdf => match iter.next().and_then(|v| APageSize::from_name(v)) {
+                Some(v) => parsed.page = Some(v),
+                None => return Err(format!("'{}' requires fit, a4, a3 or letter", arg)),
+            },
+            "--margin" if is_pdf => {
+                let value = iter.next().and_then(|v| v.parse::<f32>().ok());
+                match value.filter(|v| *v >= 0.0 && v.is_finite()) {
+                    Some(v) => parsed.margin = Some(v),
+                    None => return Err(format!("'{}' requires millimeters", arg)),
+                }
+            }
+            "--snippet" if is_html => parsed.snippet = true,
+            "--animate" if command == ACommand::Svg => parsed.animate = true,
+            "--steps" if command == ACommand::Svg => parsed.steps = true,
+            "-t" | "--to" if is_export => {
+                match iter.next().and_then(|v| AExportFormat::from_name(v)) {
+                    Some(v) => export_format = Some(v),
+                    None => {
+                        return Err(format!(
+                            "'{}' requires mermaid, dot, json, drawio or excalidraw",
+                            arg
+                        ))
+                    }
+                }
+            }
+            "-f" | "--format" if is_doc => match iter.next().map(|v| v.as_str()) {
+                Some("md") | Some("markdown") => parsed.format = Some(ADocFormat::Markdown),
+                Some("adoc") | Some("asciidoc") => parsed.format = Some(ADocFormat::Asciidoc),
+                _ => return Err(format!("'{}' requires md or adoc", arg)),
+            },
+            v if v.starts_with('-') && v != "-" => {
+                return Err(format!("unknown option '{}'", v));
+            }
+            v => parsed.inputs.push(v.to_string()),
+        }
+    }
+    if is_lsp {
+        if parsed.output.is_some() || !parsed.inputs.is_empty() {
+            return Err("'lsp' communicates over stdin/stdout only".to_string());
+        }
+        return Ok(AParsed::Run(parsed));
+    }
+    if is_watch {
+        if parsed.output.is_some() {
+            return Err("'watch' writes documents in place, '--output' is not allowed".to_string());
+        }
+        return Ok(AParsed::Run(parsed));
+    }
+    if is_export {
+        let ext = parsed.output.as_deref().and_then(|v| v.rsplit_once('.'));
+        match export_format.or(ext.and_then(|(_, ext)| AExportFormat::from_name(ext))) {
+            Some(v) => parsed.command = ACommand::Export(v),
+            None => {
+                return Err(
+                    "'export' requires '--to' or an output file with known extension".to_string(),
+                )
+            }
+        }
+    }
+    if (parsed.animate || parsed.steps) && !parsed.mermaid {
+        return Err("'--animate' and '--steps' require '--mermaid'".to_string());
+    }
+    if parsed.animate && parsed.steps {
+        return Err("'--animate' can not be used with '--steps'".to_string());
+    }
+    if parsed.steps && matches!(parsed.output.as_deref(), None | Some("-")) {
+        return Err("'--steps' requires an output file".to_string());
+    }
+    if parsed.inputs.len() > 1 && !parsed.in_place && !parsed.check {
+        return Err("only one input file is allowed".to_string());
+    }
+    if parsed.check && (parsed.in_place || parsed.output.is_some()) {
+        return Err("'--check' can not be used with '--in-place' or '--output'".to_string());
+    }
+    if parsed.in_place && (parsed.output.is_some() || parsed.inputs.is_empty()) {
+        return Err("'--in-place' requires input files and no output".to_string());
+    }
+    Ok(AParsed::Run(parsed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert!(is_cli(&to_args("svg a.txt")));
+        assert!(!is_cli(&to_args("--gapplication-service")));
+        assert_eq!(parse_args(&to_args("--version")), Ok(AParsed::Version));
+        match parse_args(&to_args("svg -m in.mmd -o out.svg")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.command, ACommand::Svg);
+                assert_eq!(args.inputs, vec!["in.mmd"]);
+                assert_eq!(args.output.as_deref(), Some("out.svg"));
+                assert!(args.mermaid && !args.expand_mode);
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        match parse_args(&to_args("doc -i --svg -f adoc a.md b.md")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.inputs.len(), 2);
+                assert_eq!(args.format, Some(ADocFormat::Asciidoc));
+                assert!(args.in_place && args.svg_file);
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("doc -i")).is_err());
+        assert!(parse_args(&to_args("doc --check -i a.md")).is_err());
+        assert!(parse_args(&to_args("doc --check a.md b.adoc")).is_ok());
+        match parse_args(&to_args("watch --debounce 50 docs src")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!((args.debounce, args.inputs.len()), (Some(50), 2))
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("watch --debounce x")).is_err());
+        match parse_args(&to_args("svgbob -c ci/asciibox.toml")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.config_path.as_deref(), Some("ci/asciibox.toml"))
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        match parse_args(&to_args("export in.mmd -o out.gv")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.command, ACommand::Export(AExportFormat::Dot))
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        match parse_args(&to_args("export --to json -o out.gv")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.command, ACommand::Export(AExportFormat::Json))
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        match parse_args(&to_args("export in.mmd -o out.excalidraw")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.command, ACommand::Export(AExportFormat::Excalidraw))
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        match parse_args(&to_args("png -m --dpi 192 --background transparent")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!(args.scale, Some(2.0));
+                assert_eq!(args.background.as_deref(), Some("transparent"));
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("png --scale 0")).is_err());
+        match parse_args(&to_args("pdf -m --page A4 --margin 5 a.mmd")) {
+            Ok(AParsed::Run(args)) => {
+                assert_eq!((args.page, args.margin), (Some(APageSize::A4), Some(5.0)))
+            }
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("pdf --page b5")).is_err());
+        match parse_args(&to_args("html -m --snippet a.mmd")) {
+            Ok(AParsed::Run(args)) => assert!(args.mermaid && args.snippet),
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("svg --snippet")).is_err());
+        match parse_args(&to_args("svg -m --steps a.mmd -o a.svg")) {
+            Ok(AParsed::Run(args)) => assert!(args.steps && !args.animate),
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("svg --animate a.txt")).is_err());
+        assert!(parse_args(&to_args("svg -m --steps a.mmd")).is_err());
+        match parse_args(&to_args("svg --set font-size=16 --transparent")) {
+            Ok(AParsed::Run(args)) => assert_eq!(args.svg_set.len(), 2),
+            v => panic!("unexpected {:?}", v),
+        }
+        assert!(parse_args(&to_args("svg --set font_size=big")).is_err());
+        assert!(parse_args(&to_args("svgbob --transparent")).is_err());
+        assert!(parse_args(&to_args("svg --scale 2")).is_err());
+        assert!(parse_args(&to_args("export --to png")).is_err());
+        assert!(parse_args(&to_args("export -o out.txt")).is_err());
+        assert!(parse_args(&to_args("lsp")).is_ok());
+        assert!(parse_args(&to_args("lsp a.md")).is_err());
+        assert!(parse_args(&to_args("doc a.md b.md")).is_err());
+        assert!(parse_args(&to_args("svg --svg")).is_err());
+        assert!(parse_args(&to_args("table -e")).is_err());
+        assert!(parse_args(&to_args("mermaid -e")).is_err());
+        assert!(parse_args(&to_args("svgbob a b")).is_err());
+        assert!(parse_args(&to_args("svgbob -o")).is_err());
+    }
+}
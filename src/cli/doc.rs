@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::Path;
+
+use similar::TextDiff;
+
+use super::args::AArgs;
+use super::{diagram_options, read_input, svg_options, write_output, EXIT_FAILURE, EXIT_OK};
+use crate::core::document::{ADocFormat, ADocProcessor, AEmbedMode};
+
+fn unified_diff(name: &str, old: &str, new: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(
+            format!("a/{}", name).as_str(),
+            format!("b/{}", name).as_str(),
+        )
+        .to_string()
+}
+
+// 处理单个文档，返回文档或 svg 文件是否有变化，检查模式下即为过期
+pub fn process_file(args: &AArgs, input: Option<&str>) -> Result<bool, String> {
+    let name = input.unwrap_or("-");
+    let content = read_input(input).map_err(|e| format!("{}: {}", name, e))?;
+    let format = match (args.format, input) {
+        (Some(v), _) => v,
+        (None, Some(path)) => ADocFormat::from_path(path),
+        (None, None) => ADocFormat::Markdown,
+    };
+    let mut processor = ADocProcessor::new(format);
+    processor.options = diagram_options(args);
+    processor.svg_options = svg_options(args);
+    processor.format_tables = args.config.doc.format_tables;
+    processor.table_width = args.config.table.max_width;
+    // svg 文件与文档放在同一目录，引用使用相对路径
+    let mut dir = Path::new(".");
+    if let Some(path) = input.filter(|&v| v != "-").map(Path::new) {
+        dir = path.parent().unwrap_or(dir);
+        if let Some(stem) = path.file_stem() {
+            processor.svg_prefix = stem.to_string_lossy().to_string();
+        }
+    }
+    if args.svg_file || args.config.doc.svg {
+        processor.mode = AEmbedMode::SvgFile;
+    }
+
+    let result = processor.do_process(content.as_str());
+    if args.check {
+        let mut stale = false;
+        if result != content {
+            print!("{}", unified_diff(name, content.as_str(), result.as_str()));
+            stale = true;
+        }
+        for file in processor.files.iter() {
+            let path = dir.join(file.path.as_str());
+            if fs::read_to_string(&path).ok().as_deref() != Some(file.content.as_str()) {
+                println!("{}: svg file is stale", path.display());
+                stale = true;
+            }
+        }
+        return Ok(stale);
+    }
+
+    // 内容不变时不写入，避免修改文件时间，watch 也不会因此再次触发
+    let mut changed = result != content;
+    for file in processor.files.iter() {
+        let path = dir.join(file.path.as_str());
+        if fs::read_to_string(&path).ok().as_deref() == Some(file.content.as_str()) {
+            continue;
+        }
+        fs::write(&path, file.content.as_str())
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        changed = true;
+    }
+    if args.in_place {
+        if result != content {
+            fs::write(name, result.as_str()).map_err(|e| format!("{}: {}", name, e))?;
+        }
+        return Ok(changed);
+    }
+    let output = args.output.as_deref();
+    write_output(output, result.as_bytes())
+        .map_err(|e| format!("{}: {}", output.unwrap_or("-"), e))?;
+    Ok(false)
+}
+
+// 逐个处理文档，单个文件出错时继续处理其余文件
+pub fn run_doc(args: &AArgs) -> i32 {
+    let inputs: Vec<Option<&str>> = match args.inputs.is_empty() {
+        true => vec![None],
+        false => args.inputs.iter().map(|v| Some(v.as_str())).collect(),
+    };
+    let mut code = EXIT_OK;
+    let mut stale = 0;
+    for input in inputs {
+        match process_file(args, input) {
+            Ok(true) if args.check => stale += 1,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("asciibox: {}", e);
+                code = EXIT_FAILURE;
+            }
+        }
+    }
+    if stale > 0 {
+        eprintln!(
+            "asciibox: {} file(s) out of date, run `asciibox doc -i` to update",
+            stale
+        );
+        code = EXIT_FAILURE;
+    }
+    code
+}
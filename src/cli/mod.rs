@@ -0,0 +1,304 @@
+mod args;
+mod doc;
+mod watch;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::core::config::AConfig;
+use crate::core::diagram::{self, DiagramOptions};
+use crate::core::export::{
+    self, ascii_to_svg, svg_to_pdf, svg_to_png, to_animated_svg, to_html, to_step_svgs,
+    AHtmlOptions, APdfOptions, APngOptions, ASvgOptions,
+};
+use crate::core::import::ascii_to_mermaid;
+use crate::core::svgbob::AMap;
+#[cfg(feature = "lsp")]
+use crate::lsp;
+use args::{parse_args, AArgs, ACommand, AParsed};
+
+pub use args::is_cli;
+
+// 退出码
+const EXIT_OK: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+// --animate 时相邻两步的间隔，单位秒
+const STEP_INTERVAL: f32 = 1.0;
+
+const USAGE: &str = "Usage: asciibox <COMMAND> [OPTIONS] [FILE]...
+
+Commands:
+  svgbob    transform mermaid to svgbob ascii diagram
+  svg       transform svgbob ascii diagram to svg
+  png       transform svgbob ascii diagram to png, rendered without display
+  pdf       transform svgbob ascii diagram to vector pdf for printing
+  html      transform svgbob ascii diagram to self-contained html with svg, ascii and source
+  table     beautify asciidoc table
+  mermaid   transform svgbob ascii diagram back to mermaid
+  export    export flowchart or dot graph as mermaid, dot, json, draw.io or excalidraw
+  doc       insert or refresh rendering after mermaid blocks in markdown/asciidoc
+  watch     run doc in place whenever documents under DIR change (linux only)
+  lsp       run language server over stdin/stdout for mermaid in editors
+
+Options:
+  -o, --output <FILE>  write to FILE instead of stdout
+  -c, --config <FILE>  use FILE instead of asciibox.toml found from current directory upward
+  -e, --expand         expand boxes in the same column (svgbob, svg, doc, watch)
+  -m, --mermaid        input is mermaid, transform to ascii first (svg, png, pdf, html)
+  -s, --set <K=V>      svg style, keys as [svg] in asciibox.toml, e.g. font_size=16 (svg, png, pdf, html, doc, watch)
+      --transparent    no svg background, same as --set transparent=true (svg, png, pdf, html, doc, watch)
+      --scale <N>      scale the image, default 1 (png)
+      --dpi <N>        same as --scale N/96 (png)
+      --background <C> background color, #rrggbb or transparent, default white (png)
+      --page <SIZE>    fit, a4, a3 or letter, default fit (pdf)
+      --margin <MM>    page margin in millimeters, default 10 (pdf)
+      --snippet        write a <figure> to paste into other pages instead of a full page (html)
+      --animate        nodes and edges fade in one step after another, in source order or by '%% step N' (svg -m)
+      --steps          write one svg per step as FILE-1.svg, FILE-2.svg ... for slides (svg -m)
+  -i, --in-place       rewrite input files, allow multiple files (doc)
+      --svg            write svg files and insert image references (doc, watch)
+      --check          print diff and exit with 1 when rendering is stale (doc)
+      --debounce <MS>  wait until no change for MS milliseconds, default 300 (watch)
+  -t, --to <FMT>       mermaid, dot, json, drawio or excalidraw, default by output extension (export)
+  -f, --format <FMT>   document format, md or adoc, default by extension (doc)
+  -h, --help           print help
+  -V, --version        print version
+
+Read from stdin when FILE is missing or '-'.
+Run without command to start the graphical application.
+";
+
+fn read_input(input: Option<&str>) -> io::Result<String> {
+    let mut content = String::new();
+    match input {
+        None | Some("-") => {
+            io::stdin().read_to_string(&mut content)?;
+        }
+        Some(path) => content = fs::read_to_string(path)?,
+    }
+    Ok(content)
+}
+
+fn write_output(output: Option<&str>, content: &[u8]) -> io::Result<()> {
+    match output {
+        None | Some("-") => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(content)?;
+            stdout.flush()
+        }
+        Some(path) => fs::write(path, content),
+    }
+}
+
+// 配置文件中的渲染选项，命令行的 --expand 优先
+fn diagram_options(args: &AArgs) -> DiagramOptions {
+    let mut options = args.config.diagram_options();
+    options.expand_mode |= args.expand_mode;
+    options
+}
+
+fn load_config(args: &AArgs) -> Result<AConfig, String> {
+    match args.config_path.as_deref() {
+        Some(path) => AConfig::load(Path::new(path)),
+        None => {
+            let dir = env::current_dir().map_err(|e| e.to_string())?;
+            AConfig::load_from(dir.as_path())
+        }
+    }
+}
+
+// 配置文件中的 svg 样式，命令行的 --set 优先
+fn svg_options(args: &AArgs) -> ASvgOptions {
+    let mut options = args.config.svg_options();
+    for (key, value) in args.svg_set.iter() {
+        // 解析参数时已经检查过
+        options.set(key, value).unwrap();
+    }
+    options
+}
+
+// svg/png/pdf/html 的输入默认为 ascii 图，-m 时先从 mermaid 生成
+fn transform_svg(args: &AArgs, content: &str) -> String {
+    let options = svg_options(args);
+    if args.mermaid {
+        // 流程图的 style/classDef/class 通过 svgbob 的 css 标签着色
+        let diagram = DiagramOptions {
+            css_tags: true,
+            ..diagram_options(args)
+        };
+        ascii_to_svg(diagram::render(content, &diagram).as_str(), &options)
+    } else {
+        ascii_to_svg(content, &options)
+    }
+}
+
+// ascii 图放在 <pre> 中，-m 时同时包含 mermaid 输入，标题为输入文件名
+fn transform_html(args: &AArgs, content: &str) -> String {
+    let (ascii, source) = match args.mermaid {
+        true => (diagram::render(content, &diagram_options(args)), content),
+        false => (content.to_string(), ""),
+    };
+    let title = args
+        .inputs
+        .first()
+        .filter(|v| v.as_str() != "-")
+        .and_then(|v| Path::new(v).file_stem())
+        .map(|v| v.to_string_lossy().to_string());
+    let options = AHtmlOptions {
+        snippet: args.snippet,
+        title: title.unwrap_or(AHtmlOptions::default().title),
+    };
+    let svg = transform_svg(args, content);
+    to_html(svg.as_str(), ascii.as_str(), source, &options)
+}
+
+// 分步显示的流程图，与 -m 生成 svg 时相同，节点颜色通过 css 标签设置
+fn step_map(args: &AArgs, content: &str) -> Result<AMap, String> {
+    let diagram = DiagramOptions {
+        css_tags: true,
+        ..diagram_options(args)
+    };
+    diagram::parse_map(content, &diagram)
+}
+
+// --steps 时每一步写入一个文件，文件名为输出文件名加上序号
+fn write_steps(args: &AArgs, content: &str) -> Result<(), String> {
+    let map = step_map(args, content)?;
+    let output = match args.output.as_deref() {
+        Some(v) => Path::new(v),
+        None => return Err("'--steps' requires an output file".to_string()),
+    };
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    for (i, svg) in to_step_svgs(&map, &svg_options(args))?.iter().enumerate() {
+        let path = output.with_file_name(format!("{}-{}.svg", stem, i + 1));
+        fs::write(&path, svg).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// 配置文件中的 png 选项，命令行优先
+fn png_options(args: &AArgs) -> APngOptions {
+    let mut options = args.config.png_options();
+    if let Some(scale) = args.scale {
+        options.scale = scale;
+    }
+    if args.background.is_some() {
+        options.background = args.background.clone();
+    }
+    options
+}
+
+// 配置文件中的 pdf 选项，命令行优先
+fn pdf_options(args: &AArgs) -> APdfOptions {
+    let mut options = args.config.pdf_options();
+    if let Some(page) = args.page {
+        options.page = page;
+    }
+    if let Some(margin) = args.margin {
+        options.margin = margin;
+    }
+    options
+}
+
+fn transform_table(args: &AArgs, content: &str) -> Result<String, String> {
+    let result = args.config.table_formator().do_format(content);
+    if result.is_empty() {
+        return Err("table needs a header line and at least one row".to_string());
+    }
+    Ok(result)
+}
+
+fn read_content(args: &AArgs) -> Result<String, String> {
+    let input = args.inputs.first().map(|v| v.as_str());
+    read_input(input).map_err(|e| format!("{}: {}", input.unwrap_or("-"), e))
+}
+
+// 读取输入，转换后写入输出文件或者标准输出
+fn convert<F>(args: &AArgs, f: F) -> Result<(), String>
+where
+    F: FnOnce(&str) -> Result<Vec<u8>, String>,
+{
+    let result = f(read_content(args)?.as_str())?;
+    let name = args.output.as_deref().unwrap_or("-");
+    write_output(args.output.as_deref(), result.as_slice()).map_err(|e| format!("{}: {}", name, e))
+}
+
+// 每个子命令对应的处理，返回退出码
+fn execute(args: &AArgs) -> i32 {
+    let options = diagram_options(args);
+    let result = match args.command {
+        ACommand::Svgbob => convert(args, |c| Ok(diagram::render(c, &options).into_bytes())),
+        ACommand::Svg if args.steps => {
+            read_content(args).and_then(|c| write_steps(args, c.as_str()))
+        }
+        ACommand::Svg if args.animate => convert(args, |c| {
+            let map = step_map(args, c)?;
+            Ok(to_animated_svg(&map, &svg_options(args), STEP_INTERVAL)?.into_bytes())
+        }),
+        ACommand::Svg => convert(args, |c| Ok(transform_svg(args, c).into_bytes())),
+        ACommand::Png => convert(args, |c| {
+            svg_to_png(transform_svg(args, c).as_str(), &png_options(args))
+        }),
+        ACommand::Pdf => convert(args, |c| {
+            svg_to_pdf(transform_svg(args, c).as_str(), &pdf_options(args))
+        }),
+        ACommand::Html => convert(args, |c| Ok(transform_html(args, c).into_bytes())),
+        ACommand::Table => convert(args, |c| Ok(transform_table(args, c)?.into_bytes())),
+        ACommand::Mermaid => convert(args, |c| Ok(ascii_to_mermaid(c).into_bytes())),
+        ACommand::Export(format) => convert(args, |c| {
+            Ok(export::export(c, format, &options)?.into_bytes())
+        }),
+        ACommand::Doc => return doc::run_doc(args),
+        ACommand::Watch => return watch::run_watch(args),
+        #[cfg(feature = "lsp")]
+        ACommand::Lsp => lsp::run_stdio(args.config.clone()).map_err(|e| format!("lsp: {}", e)),
+        #[cfg(not(feature = "lsp"))]
+        ACommand::Lsp => Err("lsp: built without the lsp feature".to_string()),
+    };
+    match result {
+        Ok(()) => EXIT_OK,
+        Err(e) => {
+            eprintln!("asciibox: {}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+// 以命令行方式运行，返回退出码，args 不包含程序名
+pub fn run(args: &[String]) -> i32 {
+    let parsed = match parse_args(args) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("asciibox: {}\n\n{}", e, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    let parsed = match parsed {
+        AParsed::Run(mut args) => match load_config(&args) {
+            Ok(v) => {
+                args.config = v;
+                AParsed::Run(args)
+            }
+            Err(e) => {
+                eprintln!("asciibox: {}", e);
+                return EXIT_FAILURE;
+            }
+        },
+        v => v,
+    };
+    match parsed {
+        AParsed::Help => {
+            print!("{}", USAGE);
+            EXIT_OK
+        }
+        AParsed::Version => {
+            println!("asciibox {}", env!("CARGO_PKG_VERSION"));
+            EXIT_OK
+        }
+        AParsed::Run(args) => execute(&args),
+    }
+}
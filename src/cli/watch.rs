@@ -0,0 +1,265 @@
+use super::args::AArgs;
+use super::EXIT_FAILURE;
+
+#[cfg(target_os = "linux")]
+mod inner {
+    use std::collections::{BTreeSet, HashMap};
+    use std::ffi::OsString;
+    use std::fs;
+    use std::io::{self, ErrorKind};
+    use std::path::{Path, PathBuf};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+
+    use super::super::doc::process_file;
+    use super::{AArgs, EXIT_FAILURE};
+
+    // 需要处理的文档扩展名
+    const EXTENSIONS: [&str; 5] = ["md", "markdown", "adoc", "asciidoc", "asc"];
+
+    pub(super) fn is_document(path: &Path) -> bool {
+        match path.extension() {
+            Some(ext) => EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()),
+            None => false,
+        }
+    }
+
+    // 跳过隐藏目录和编译输出目录
+    pub(super) fn is_ignored_dir(path: &Path) -> bool {
+        match path.file_name() {
+            Some(name) => {
+                let name = name.to_string_lossy();
+                name.starts_with('.') || name == "target"
+            }
+            None => false,
+        }
+    }
+
+    // 待处理的文档，连续修改时等到安静 debounce 时间之后一起处理
+    pub(super) struct APending {
+        paths: BTreeSet<PathBuf>,
+        debounce: Duration,
+        last: Instant,
+    }
+
+    impl APending {
+        pub(super) fn new(debounce: Duration) -> Self {
+            Self {
+                paths: BTreeSet::new(),
+                debounce,
+                last: Instant::now(),
+            }
+        }
+
+        pub(super) fn is_empty(&self) -> bool {
+            self.paths.is_empty()
+        }
+
+        pub(super) fn insert(&mut self, path: PathBuf, now: Instant) {
+            self.paths.insert(path);
+            self.last = now;
+        }
+
+        // 删除或移走的文件，目录被删除时去掉其中所有的文件
+        pub(super) fn remove(&mut self, path: &Path) {
+            self.paths.retain(|p| !p.starts_with(path));
+        }
+
+        // 距最后一次修改超过 debounce 时取出所有待处理文档
+        pub(super) fn take_ready(&mut self, now: Instant) -> Option<BTreeSet<PathBuf>> {
+            if self.paths.is_empty() || now.duration_since(self.last) < self.debounce {
+                return None;
+            }
+            Some(std::mem::take(&mut self.paths))
+        }
+    }
+
+    struct AWatcher {
+        inotify: Inotify,
+        dirs: HashMap<WatchDescriptor, PathBuf>,
+    }
+
+    impl AWatcher {
+        fn new() -> io::Result<Self> {
+            Ok(Self {
+                inotify: Inotify::init()?,
+                dirs: HashMap::new(),
+            })
+        }
+
+        // inotify 不支持递归，逐个目录添加，返回目录中已有的文档
+        fn add_dir(&mut self, dir: &Path, docs: &mut Vec<PathBuf>) {
+            let mask = WatchMask::CLOSE_WRITE
+                | WatchMask::MOVED_TO
+                | WatchMask::CREATE
+                | WatchMask::DELETE
+                | WatchMask::MOVED_FROM;
+            match self.inotify.watches().add(dir, mask) {
+                Ok(wd) => {
+                    self.dirs.insert(wd, dir.to_path_buf());
+                }
+                Err(e) => {
+                    eprintln!("asciibox: {}: {}", dir.display(), e);
+                    return;
+                }
+            }
+            let entries = match fs::read_dir(dir) {
+                Ok(v) => v,
+                Err(e) => {
+                    eprintln!("asciibox: {}: {}", dir.display(), e);
+                    return;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() && !is_ignored_dir(&path) {
+                    self.add_dir(&path, docs);
+                } else if is_document(&path) {
+                    docs.push(path);
+                }
+            }
+        }
+    }
+
+    // 单个文件出错只输出错误，不退出
+    fn process(args: &AArgs, paths: &BTreeSet<PathBuf>) {
+        for path in paths.iter().filter(|p| p.is_file()) {
+            let name = path.to_string_lossy();
+            match process_file(args, Some(name.as_ref())) {
+                Ok(true) => println!("updated {}", name),
+                Ok(false) => {}
+                Err(e) => eprintln!("asciibox: {}", e),
+            }
+        }
+    }
+
+    pub fn run_watch(args: &AArgs) -> i32 {
+        let mut args = args.clone();
+        args.in_place = true;
+        let roots: Vec<PathBuf> = match args.inputs.is_empty() {
+            true => vec![PathBuf::from(".")],
+            false => args.inputs.iter().map(PathBuf::from).collect(),
+        };
+        let mut watcher = match AWatcher::new() {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("asciibox: inotify: {}", e);
+                return EXIT_FAILURE;
+            }
+        };
+        let mut docs: Vec<PathBuf> = Vec::new();
+        for root in roots.iter() {
+            if !root.is_dir() {
+                eprintln!("asciibox: {}: not a directory", root.display());
+                return EXIT_FAILURE;
+            }
+            watcher.add_dir(root, &mut docs);
+        }
+        // 启动时先处理一遍已有文档
+        process(&args, &docs.into_iter().collect());
+        eprintln!("asciibox: watching for changes, press Ctrl+C to stop");
+
+        let debounce = args.debounce.unwrap_or(args.config.watch.debounce);
+        let mut pending = APending::new(Duration::from_millis(debounce));
+        let mut buffer = [0u8; 4096];
+        loop {
+            // 没有待处理文件时阻塞等待，否则轮询直到安静下来
+            let events = match pending.is_empty() {
+                true => watcher.inotify.read_events_blocking(&mut buffer),
+                false => watcher.inotify.read_events(&mut buffer),
+            };
+            let events: Vec<(WatchDescriptor, EventMask, Option<OsString>)> = match events {
+                Ok(v) => v
+                    .map(|e| (e.wd, e.mask, e.name.map(|n| n.to_os_string())))
+                    .collect(),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    match pending.take_ready(Instant::now()) {
+                        Some(paths) => process(&args, &paths),
+                        None => sleep(Duration::from_millis(20)),
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("asciibox: inotify: {}", e);
+                    return EXIT_FAILURE;
+                }
+            };
+            for (wd, mask, name) in events {
+                let path = match (watcher.dirs.get(&wd), name) {
+                    (Some(dir), Some(name)) => dir.join(name),
+                    _ => continue,
+                };
+                let now = Instant::now();
+                if mask.intersects(EventMask::DELETE | EventMask::MOVED_FROM) {
+                    pending.remove(&path);
+                } else if mask.contains(EventMask::ISDIR) {
+                    if !is_ignored_dir(&path) {
+                        let mut docs: Vec<PathBuf> = Vec::new();
+                        watcher.add_dir(&path, &mut docs);
+                        for doc in docs {
+                            pending.insert(doc, now);
+                        }
+                    }
+                } else if is_document(&path) {
+                    pending.insert(path, now);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use inner::run_watch;
+
+#[cfg(not(target_os = "linux"))]
+pub fn run_watch(_args: &AArgs) -> i32 {
+    eprintln!("asciibox: watch is only supported on linux");
+    EXIT_FAILURE
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
+
+    use super::inner::{is_document, is_ignored_dir, APending};
+
+    #[test]
+    fn test_pending() {
+        assert!(is_document(Path::new("docs/a.md")));
+        assert!(is_document(Path::new("b.ADOC")));
+        assert!(!is_document(Path::new("a.svg")) && !is_document(Path::new("README")));
+        assert!(is_ignored_dir(Path::new("src/.git")));
+        assert!(is_ignored_dir(Path::new("target")));
+        assert!(!is_ignored_dir(Path::new("docs")));
+
+        let start = Instant::now();
+        let ms = |v: u64| start + Duration::from_millis(v);
+        let mut pending = APending::new(Duration::from_millis(300));
+        pending.insert(PathBuf::from("a.md"), ms(0));
+        pending.insert(PathBuf::from("docs/b.md"), ms(200));
+        pending.insert(PathBuf::from("docs/c.md"), ms(250));
+        // 每次修改重新计时
+        assert_eq!(pending.take_ready(ms(400)), None);
+        pending.remove(Path::new("docs/b.md"));
+        let paths = pending.take_ready(ms(600)).unwrap();
+        assert_eq!(
+            paths.into_iter().collect::<Vec<_>>(),
+            vec![PathBuf::from("a.md"), PathBuf::from("docs/c.md")]
+        );
+        assert!(pending.is_empty());
+        assert_eq!(pending.take_ready(ms(1000)), None);
+
+        // 删除目录时去掉其中的文档
+        pending.insert(PathBuf::from("docs/c.md"), ms(1000));
+        pending.insert(PathBuf::from("docs.md"), ms(1000));
+        pending.remove(Path::new("docs"));
+        let paths = pending.take_ready(ms(2000)).unwrap();
+        assert_eq!(
+            paths.into_iter().collect::<Vec<_>>(),
+            vec![PathBuf::from("docs.md")]
+        );
+    }
+}
@@ -3,6 +3,22 @@ pub struct TableFormator {
     pub title: String,
     pub w: usize,
     pub max_w: usize,
+    // 首行之后有空行，asciidoc 中表示首行为表头
+    pub header: bool,
+}
+
+// asciidoc 表格首尾的 |=== 分隔行
+fn is_border(line: &str) -> bool {
+    match line.trim().strip_prefix("|=") {
+        Some(v) => v.chars().all(|c| c == '='),
+        None => false,
+    }
+}
+
+impl Default for TableFormator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TableFormator {
@@ -11,6 +27,7 @@ impl TableFormator {
             title: "".to_string(),
             w: 24,
             max_w: 80,
+            header: false,
         }
     }
 
@@ -18,10 +35,11 @@ impl TableFormator {
         if !self.check_content(input) {
             return "".to_string();
         }
+        self.title.clear();
+        self.header = self.check_header(input);
         let lines: Vec<&str> = input.split('\n').filter(|&s| !s.is_empty()).collect();
         let adata = self.prepare_content(lines);
-        let result = self.format_content(adata);
-        return result;
+        self.format_content(adata)
     }
 
     fn check_content(&self, input: &str) -> bool {
@@ -29,7 +47,26 @@ impl TableFormator {
         if lines.len() < 2 {
             return false;
         }
-        return true;
+        true
+    }
+
+    fn check_header(&self, input: &str) -> bool {
+        let lines: Vec<&str> = input
+            .split('\n')
+            .map(|s| s.trim())
+            .filter(|&s| !is_border(s))
+            .skip_while(|s| s.is_empty())
+            .collect();
+        let start = match lines.first() {
+            Some(v) if v.starts_with('.') => 1,
+            _ => 0,
+        };
+        let rows: Vec<&str> = lines[start..]
+            .iter()
+            .copied()
+            .skip_while(|s| s.is_empty())
+            .collect();
+        rows.len() > 2 && rows[1].is_empty() && rows[2..].iter().any(|s| !s.is_empty())
     }
 
     fn prepare_content(&mut self, lines: Vec<&str>) -> Vec<Vec<String>> {
@@ -46,7 +83,7 @@ impl TableFormator {
                 continue;
             }
             let nline: String = line.trim().to_string();
-            if nline.len() == 0 {
+            if nline.is_empty() || is_border(nline.as_str()) {
                 continue;
             }
             // 如果是 asciidoc 或者 md 表格
@@ -108,12 +145,17 @@ impl TableFormator {
         }
         let mut border = String::new();
         border.push('|');
-        border.push_str("=".repeat(std::cmp::min(self.max_w, total_w)).as_str());
+        // asciidoc 的表格分隔符至少为 |===
+        let border_w = std::cmp::max(3, std::cmp::min(self.max_w, total_w));
+        border.push_str("=".repeat(border_w).as_str());
+        if self.header && content.len() > 1 {
+            content.insert(1, String::new());
+        }
         content.insert(0, border.clone());
         content.push(border);
 
         // 添加标题
-        if self.title.len() > 0 {
+        if !self.title.is_empty() {
             content.insert(0, self.title.clone() + "\n");
         }
 
@@ -125,3 +167,28 @@ impl TableFormator {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_table() {
+        let mut formator = TableFormator::new();
+        let input = ".Title\n|===\n| a | bb\n\n|1|2\n| 333 | 4\n|===\n";
+        let result = ".Title
+|=========
+| a   | bb
+
+| 1   | 2
+| 333 | 4
+|=========
+";
+        assert_eq!(formator.do_format(input), result);
+        assert_eq!(formator.do_format(result), result);
+        assert_eq!(
+            formator.do_format("a|b\nc|d"),
+            "|======\n| a | b\n| c | d\n|======\n"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod pie;
+mod xychart;
+
+pub use pie::APie;
+pub use xychart::{invalid_values, parse_axis, AXyChart};
@@ -0,0 +1,266 @@
+use crate::core::utils::{cn_length, ACanvas};
+use std::f64::consts::PI;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASlice {
+    pub label: String,
+    pub value: f64,
+}
+
+// mermaid pie 图
+// pie showData title xxx
+//     "label" : value
+#[derive(Debug, Clone)]
+pub struct APie {
+    pub title: String,
+    pub show_data: bool,
+    pub slices: Vec<ASlice>,
+    // 圆的半径(行数)
+    radius: usize,
+}
+
+impl APie {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            show_data: false,
+            slices: Vec::new(),
+            radius: 5,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.title = String::new();
+        self.show_data = false;
+        self.slices = Vec::new();
+    }
+
+    fn parse_header(&mut self, line: &str) {
+        let mut remain = line.trim();
+        loop {
+            if let Some(v) = remain.strip_prefix("showData") {
+                self.show_data = true;
+                remain = v.trim();
+            } else if let Some(v) = remain.strip_prefix("title") {
+                self.title = v.trim().to_string();
+                return;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        if let Some(v) = line.strip_prefix("pie") {
+            self.parse_header(v);
+            return;
+        }
+        if line.starts_with("title") || line.starts_with("showData") {
+            self.parse_header(line);
+            return;
+        }
+        let (label, value) = match line.rsplit_once(':') {
+            Some(v) => v,
+            None => return,
+        };
+        let value: f64 = match value.trim().parse() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        // NaN 和 inf 无法计算比例
+        if value < 0.0 || !value.is_finite() {
+            return;
+        }
+        let label = label.trim().trim_matches('"').trim().to_string();
+        self.slices.push(ASlice { label, value });
+    }
+
+    pub fn build_slices(&mut self, content: &str) {
+        self.clear();
+        for line in content.split('\n') {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("%%") {
+                continue;
+            }
+            self.parse_line(line);
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.slices.iter().map(|s| s.value).sum()
+    }
+
+    // 每行圆的半宽，字符高宽比约为 2:1，所以横向放大一倍
+    fn half_width(&self, dy: i64) -> usize {
+        let r = self.radius as f64 + 0.5;
+        let dy = dy as f64;
+        (2.0 * (r * r - dy * dy).max(0.0).sqrt()).round() as usize
+    }
+
+    fn render_circle(&self, canvas: &mut ACanvas, cx: usize, top: usize) {
+        let r = self.radius as i64;
+        for dy in -r..=r {
+            let y = (top as i64 + r + dy) as usize;
+            let hw = self.half_width(dy);
+            if dy.abs() == r {
+                let c = if dy < 0 { '.' } else { '\'' };
+                canvas.hline(cx - hw, cx + hw, y, '-');
+                canvas.put(cx - hw, y, c);
+                canvas.put(cx + hw, y, c);
+                continue;
+            }
+            // 与更靠近顶部或底部的一行比较，判断边缘的走向
+            let nw = self.half_width(dy + dy.signum());
+            let (outer, inner) = if dy < 0 { ('.', '\'') } else { ('\'', '.') };
+            let (lc, rc) = if dy < 0 { ('/', '\\') } else { ('\\', '/') };
+            if dy == 0 || hw == nw {
+                canvas.put(cx - hw, y, '|');
+                canvas.put(cx + hw, y, '|');
+            } else if hw == nw + 1 {
+                canvas.put(cx - hw, y, lc);
+                canvas.put(cx + hw, y, rc);
+            } else {
+                canvas.hline(cx - hw, cx - nw, y, '-');
+                canvas.hline(cx + nw, cx + hw, y, '-');
+                canvas.put(cx - hw, y, outer);
+                canvas.put(cx + hw, y, outer);
+                canvas.put(cx - nw, y, inner);
+                canvas.put(cx + nw, y, inner);
+            }
+        }
+    }
+
+    // 角度从 12 点钟方向开始顺时针计算，与 mermaid 一致
+    fn point_at(&self, cx: usize, cy: usize, angle: f64, t: f64) -> (usize, usize) {
+        let x = cx as f64 + 2.0 * t * angle.sin();
+        let y = cy as f64 - t * angle.cos();
+        (x.round() as usize, y.round() as usize)
+    }
+
+    fn render_slices(&self, canvas: &mut ACanvas, cx: usize, cy: usize) {
+        let total = self.total();
+        let r = self.radius as f64;
+        let mut start = 0.0;
+        let mut marks: Vec<(usize, f64)> = Vec::new();
+        for (i, slice) in self.slices.iter().enumerate() {
+            let sweep = slice.value / total * 2.0 * PI;
+            marks.push((i, start + sweep / 2.0));
+            if self.slices.len() > 1 && slice.value > 0.0 {
+                let (x, y) = self.point_at(cx, cy, start, r + 0.5);
+                canvas.line(cx, cy, x, y);
+            }
+            start += sweep;
+        }
+        if self.slices.len() > 1 {
+            canvas.put(cx, cy, '+');
+        }
+        // 在扇区中间标记序号
+        for (i, angle) in marks.iter() {
+            let mark = (i + 1).to_string();
+            for t in [0.6, 0.45, 0.75] {
+                let (x, y) = self.point_at(cx, cy, *angle, r * t);
+                let w = cn_length(mark.as_str());
+                if (x..x + w).all(|v| canvas.is_blank(v, y)) {
+                    canvas.text(x, y, mark.as_str());
+                    break;
+                }
+            }
+        }
+    }
+
+    fn render_legend(&self, canvas: &mut ACanvas, x: usize, cy: usize) {
+        let total = self.total();
+        let lw = self
+            .slices
+            .iter()
+            .map(|s| cn_length(s.label.as_str()))
+            .max()
+            .unwrap_or(0);
+        let values: Vec<String> = self.slices.iter().map(|s| format_value(s.value)).collect();
+        let vw = values.iter().map(|v| v.len()).max().unwrap_or(0);
+        let no_w = self.slices.len().to_string().len();
+        let top = cy.saturating_sub(self.slices.len() / 2);
+        for (i, slice) in self.slices.iter().enumerate() {
+            let mut line = format!("{:>w$} ", i + 1, w = no_w);
+            line.push_str(slice.label.as_str());
+            line.push_str(
+                " ".repeat(lw - cn_length(slice.label.as_str()) + 2)
+                    .as_str(),
+            );
+            if self.show_data {
+                line.push_str(format!("{:>w$}  ", values[i], w = vw).as_str());
+            }
+            line.push_str(format!("{:>5.1}%", slice.value / total * 100.0).as_str());
+            canvas.text(x, top + i, line.as_str());
+        }
+    }
+
+    pub fn render(&self) -> String {
+        if self.slices.is_empty() || self.total() <= 0.0 {
+            return String::new();
+        }
+        let mut canvas = ACanvas::new(0, 0);
+        let top = if self.title.is_empty() { 0 } else { 2 };
+        let cx = self.half_width(0);
+        let cy = top + self.radius;
+        self.render_circle(&mut canvas, cx, top);
+        self.render_slices(&mut canvas, cx, cy);
+        self.render_legend(&mut canvas, cx * 2 + 4, cy);
+        if !self.title.is_empty() {
+            let tw = cn_length(self.title.as_str());
+            canvas.text(canvas.w.saturating_sub(tw) / 2, 0, self.title.as_str());
+        }
+        canvas.render()
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.build_slices(content);
+        self.render()
+    }
+}
+
+impl Default for APie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 整数不显示小数位
+pub fn format_value(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        format!("{}", v as i64)
+    } else {
+        format!("{:.2}", v)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pie_parse() {
+        let mut pie = APie::new();
+        pie.build_slices(
+            "pie showData title 宠物\n  \"Dogs\" : 386\n \"Cats\": 85.5\n bad line\n \"x\" : NaN\n \"y\" : inf",
+        );
+        assert!(pie.show_data);
+        assert_eq!(pie.title, "宠物");
+        assert_eq!(pie.slices.len(), 2);
+        assert_eq!(pie.slices[1].value, 85.5);
+        assert_eq!(format_value(85.5), "85.5");
+        assert_eq!(format_value(386.0), "386");
+    }
+
+    #[test]
+    fn test_pie_render() {
+        let mut pie = APie::new();
+        let out = pie.load_content("pie\n\"a\" : 1\n\"b\" : 3");
+        assert!(out.contains("1 a   25.0%"));
+        assert!(out.contains("2 b   75.0%"));
+        assert!(out.starts_with("      .---------."));
+    }
+}
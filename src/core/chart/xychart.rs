@@ -0,0 +1,403 @@
+use super::pie::format_value;
+use crate::core::utils::{cn_length, ACanvas};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ASeriesKind {
+    Bar,
+    Line,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASeries {
+    pub kind: ASeriesKind,
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AAxis {
+    pub title: String,
+    pub labels: Vec<String>,
+    pub range: Option<(f64, f64)>,
+}
+
+// 类似 mermaid xychart-beta 的柱状图和折线图
+// xychart-beta
+//     title "Sales"
+//     x-axis [jan, feb, mar]
+//     y-axis "Revenue" 0 --> 100
+//     bar [10, 20, 30]
+//     line [10, 20, 30]
+#[derive(Debug, Clone)]
+pub struct AXyChart {
+    pub title: String,
+    pub x_axis: AAxis,
+    pub y_axis: AAxis,
+    pub series: Vec<ASeries>,
+    // 绘图区高度(行数)
+    plot_h: usize,
+}
+
+// 去掉两侧引号，返回 (引号内容, 剩余内容)
+fn split_quoted(input: &str) -> (String, &str) {
+    let input = input.trim();
+    if let Some(v) = input.strip_prefix('"') {
+        if let Some((text, remain)) = v.split_once('"') {
+            return (text.to_string(), remain.trim());
+        }
+    }
+    (String::new(), input)
+}
+
+fn parse_list(input: &str) -> Option<Vec<String>> {
+    let l = input.find('[')?;
+    let r = input.rfind(']')?;
+    if r < l {
+        return None;
+    }
+    Some(
+        input[l + 1..r]
+            .split(',')
+            .map(|s| s.trim().trim_matches('"').trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    )
+}
+
+// 坐标轴范围 min --> max，按原样返回，顺序错误时由诊断提示
+fn parse_range(input: &str) -> Option<(f64, f64)> {
+    let (a, b) = input.split_once("-->")?;
+    let a: f64 = a.trim().parse().ok()?;
+    let b: f64 = b.trim().parse().ok()?;
+    Some((a, b))
+}
+
+// 只接受有限的数值，NaN 和 inf 无法绘制
+fn parse_value(text: &str) -> Option<f64> {
+    text.parse().ok().filter(|v: &f64| v.is_finite())
+}
+
+// bar 或 line 序列中无法绘制的值
+pub fn invalid_values(input: &str) -> Vec<String> {
+    let (_, remain) = split_quoted(input);
+    let values = parse_list(remain).unwrap_or_default();
+    values
+        .into_iter()
+        .filter(|s| parse_value(s).is_none())
+        .collect()
+}
+
+pub fn parse_axis(input: &str) -> AAxis {
+    let (mut title, mut remain) = split_quoted(input);
+    // 不带引号的单词标题
+    if title.is_empty() && !remain.starts_with('[') && parse_range(remain).is_none() {
+        let (t, r) = remain.split_once(' ').unwrap_or((remain, ""));
+        title = t.to_string();
+        remain = r.trim();
+    }
+    AAxis {
+        title,
+        labels: parse_list(remain).unwrap_or_default(),
+        range: parse_range(remain),
+    }
+}
+
+impl AXyChart {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            x_axis: AAxis::default(),
+            y_axis: AAxis::default(),
+            series: Vec::new(),
+            plot_h: 10,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.title = String::new();
+        self.x_axis = AAxis::default();
+        self.y_axis = AAxis::default();
+        self.series = Vec::new();
+    }
+
+    fn parse_series(&mut self, kind: ASeriesKind, input: &str) {
+        let (name, remain) = split_quoted(input);
+        let values: Vec<f64> = match parse_list(remain) {
+            Some(v) => v.iter().filter_map(|s| parse_value(s)).collect(),
+            None => return,
+        };
+        self.series.push(ASeries { kind, name, values });
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let (key, remain) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "title" => {
+                let (title, other) = split_quoted(remain);
+                self.title = if title.is_empty() {
+                    other.to_string()
+                } else {
+                    title
+                };
+            }
+            "x-axis" => self.x_axis = parse_axis(remain),
+            "y-axis" => self.y_axis = parse_axis(remain),
+            "bar" => self.parse_series(ASeriesKind::Bar, remain),
+            "line" => self.parse_series(ASeriesKind::Line, remain),
+            _ => {}
+        }
+    }
+
+    pub fn build_series(&mut self, content: &str) {
+        self.clear();
+        for line in content.split('\n') {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("%%") || line.starts_with("xychart") {
+                continue;
+            }
+            self.parse_line(line);
+        }
+    }
+
+    fn count(&self) -> usize {
+        let n = self
+            .series
+            .iter()
+            .map(|s| s.values.len())
+            .max()
+            .unwrap_or(0);
+        std::cmp::max(n, self.x_axis.labels.len())
+    }
+
+    fn labels(&self) -> Vec<String> {
+        (0..self.count())
+            .map(|i| match self.x_axis.labels.get(i) {
+                Some(v) => v.clone(),
+                None => (i + 1).to_string(),
+            })
+            .collect()
+    }
+
+    // 指定的范围顺序错误时按数据计算
+    fn value_range(&self) -> (f64, f64) {
+        if let Some(v) = self.y_axis.range {
+            if v.1 > v.0 {
+                return v;
+            }
+        }
+        let values = self.series.iter().flat_map(|s| s.values.iter());
+        let lo = values.clone().fold(0.0_f64, |a, &b| a.min(b));
+        let hi = values.fold(lo, |a, &b| a.max(b));
+        if hi > lo {
+            (lo, hi)
+        } else {
+            (lo, lo + 1.0)
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let n = self.count();
+        if n == 0 {
+            return String::new();
+        }
+        let labels = self.labels();
+        let (lo, hi) = self.value_range();
+        let bars = self
+            .series
+            .iter()
+            .filter(|s| s.kind == ASeriesKind::Bar)
+            .count();
+
+        // y 轴刻度，有负数时加上 0
+        let mut values = vec![hi, (hi + lo) / 2.0, lo];
+        if lo < 0.0 && hi > 0.0 && !values.contains(&0.0) {
+            values.push(0.0);
+        }
+        let ticks: Vec<(f64, String)> = values.iter().map(|v| (*v, format_value(*v))).collect();
+        let tick_w = ticks.iter().map(|t| t.1.len()).max().unwrap_or(0);
+        let ax = tick_w + 1;
+
+        // 每个分类的宽度
+        let label_w = labels
+            .iter()
+            .map(|s| cn_length(s.as_str()))
+            .max()
+            .unwrap_or(0);
+        let group_w = std::cmp::max(std::cmp::max(label_w, bars * 5), 3);
+        let pitch = group_w + 2;
+        let start = |i: usize| ax + 2 + i * pitch;
+        let center = |i: usize| start(i) + group_w / 2;
+
+        let mut canvas = ACanvas::new(0, 0);
+        let mut top = 0;
+        if !self.title.is_empty() {
+            top += 2;
+        }
+        if !self.y_axis.title.is_empty() {
+            canvas.text(0, top, self.y_axis.title.as_str());
+            top += 1;
+        }
+        let base = top + self.plot_h;
+        let row = |v: f64| {
+            let ratio = ((v - lo) / (hi - lo)).clamp(0.0, 1.0);
+            base - (ratio * self.plot_h as f64).round() as usize
+        };
+
+        // 坐标轴
+        let right = ax + 2 + n * pitch;
+        canvas.vline(ax, top, base, '|');
+        canvas.hline(ax, right, base, '-');
+        canvas.put(ax, base, '+');
+        for (v, text) in ticks.iter() {
+            let y = row(*v);
+            canvas.text(tick_w - text.len(), y, text.as_str());
+            if y != base {
+                canvas.put(ax, y, '+');
+            }
+        }
+        // 柱子的起点，范围不包含 0 时为离 0 最近的一端
+        let zero = row(0.0);
+        if zero != base {
+            canvas.hline(ax + 1, right, zero, '-');
+        }
+        for (i, label) in labels.iter().enumerate() {
+            let w = cn_length(label.as_str());
+            canvas.text(start(i) + (group_w - w) / 2, base + 1, label.as_str());
+        }
+        if !self.x_axis.title.is_empty() {
+            let w = cn_length(self.x_axis.title.as_str());
+            canvas.text(
+                (ax + right).saturating_sub(w) / 2,
+                base + 2,
+                self.x_axis.title.as_str(),
+            );
+        }
+
+        // 柱状图，正数从 0 向上，负数从 0 向下
+        let bar_left = |i: usize, k: usize| start(i) + (group_w + 1 - bars * 5) / 2 + k * 5;
+        let bar_series = self.series.iter().filter(|s| s.kind == ASeriesKind::Bar);
+        for (k, s) in bar_series.enumerate() {
+            for (i, v) in s.values.iter().enumerate() {
+                let y = row(*v);
+                if y == zero {
+                    continue;
+                }
+                let x = bar_left(i, k);
+                let (corner, top, bottom) = match y < zero {
+                    true => ('.', y + 1, zero - 1),
+                    false => ('\'', zero + 1, y - 1),
+                };
+                canvas.hline(x + 1, x + 2, y, '-');
+                canvas.put(x, y, corner);
+                canvas.put(x + 3, y, corner);
+                if top <= bottom {
+                    canvas.vline(x, top, bottom, '|');
+                    canvas.vline(x + 3, top, bottom, '|');
+                }
+            }
+        }
+
+        // 折线图
+        let line_series = self.series.iter().filter(|s| s.kind == ASeriesKind::Line);
+        for s in line_series {
+            let points: Vec<(usize, usize)> = s
+                .values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (center(i), row(*v)))
+                .collect();
+            for p in points.windows(2) {
+                canvas.line(p[0].0, p[0].1, p[1].0, p[1].1);
+            }
+            for (x, y) in points.iter() {
+                canvas.put(*x, *y, '*');
+            }
+        }
+
+        if !self.title.is_empty() {
+            let w = cn_length(self.title.as_str());
+            canvas.text(canvas.w.saturating_sub(w) / 2, 0, self.title.as_str());
+        }
+        canvas.render()
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.build_series(content);
+        self.render()
+    }
+}
+
+impl Default for AXyChart {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xychart_parse() {
+        let mut chart = AXyChart::new();
+        chart.build_series(
+            "xychart-beta
+            title \"Sales 收入\"
+            x-axis [jan, \"feb\", mar]
+            y-axis \"Revenue\" 0 --> 100
+            bar [10, 20.5, 30]
+            line [5, 15, 25]",
+        );
+        assert_eq!(chart.title, "Sales 收入");
+        assert_eq!(chart.x_axis.labels, vec!["jan", "feb", "mar"]);
+        assert_eq!(chart.y_axis.title, "Revenue");
+        assert_eq!(chart.y_axis.range, Some((0.0, 100.0)));
+        assert_eq!(parse_range("100 --> 0"), Some((100.0, 0.0)));
+        assert_eq!(chart.series.len(), 2);
+        assert_eq!(chart.series[0].values, vec![10.0, 20.5, 30.0]);
+        assert_eq!(chart.series[1].kind, ASeriesKind::Line);
+        // NaN 和 inf 无法绘制，直接丢弃
+        chart.build_series("xychart-beta\nbar [1, NaN, inf, -infinity, 2]");
+        assert_eq!(chart.series[0].values, vec![1.0, 2.0]);
+        assert_eq!(invalid_values(" \"s\" [1, NaN, x]"), vec!["NaN", "x"]);
+    }
+
+    #[test]
+    fn test_xychart_render() {
+        let mut chart = AXyChart::new();
+        let out = chart.load_content("xychart-beta\nx-axis [a, b]\nbar [1, 2]");
+        let result = "
+2 +        .--.
+  |        |  |
+  |        |  |
+  |        |  |
+  |        |  |
+1 + .--.   |  |
+  | |  |   |  |
+  | |  |   |  |
+  | |  |   |  |
+  | |  |   |  |
+0 +----------------
+      a      b
+";
+        assert_eq!(out, result[1..]);
+
+        // 负数的柱子从 0 向下绘制
+        let out = chart.load_content("xychart-beta\nx-axis [a, b]\ny-axis -2 --> 2\nbar [2, -2]");
+        let result = "
+ 2 + .--.
+   | |  |
+   | |  |
+   | |  |
+   | |  |
+ 0 +----------------
+   |        |  |
+   |        |  |
+   |        |  |
+   |        |  |
+-2 +--------'--'----
+       a      b
+";
+        assert_eq!(out, result[1..]);
+    }
+}
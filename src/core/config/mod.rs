@@ -0,0 +1,330 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::adoc::TableFormator;
+use crate::core::diagram::{ACharset, DiagramOptions};
+use crate::core::export::{
+    parse_color, APageSize, APdfOptions, APngOptions, ASvgOptions, ASvgTheme,
+};
+use crate::core::svgbob::ADirection;
+
+// 项目配置文件名，从当前目录向上查找
+pub const CONFIG_NAME: &str = "asciibox.toml";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ARenderConfig {
+    // 扩展 box 保证同一列宽度相同
+    pub expand: bool,
+    pub charset: ACharset,
+    // 流程图 box 内文字两侧的空白
+    pub padding: usize,
+    // 流程图首行未指定方向时使用
+    pub direction: ADirection,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ATableConfig {
+    // 表格分隔行 |=== 的最大宽度
+    pub max_width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ADocConfig {
+    // 生成 svg 文件而不是 svgbob 块
+    pub svg: bool,
+    // 同时格式化 asciidoc 表格
+    pub format_tables: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AWatchConfig {
+    // 合并连续修改的等待时间，单位毫秒
+    pub debounce: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ASvgConfig {
+    pub font_family: String,
+    pub font_size: usize,
+    pub stroke_width: f32,
+    pub stroke_color: String,
+    pub fill_color: String,
+    pub background: String,
+    // 每个字符单元对应的像素
+    pub scale: f32,
+    // 不绘制背景
+    pub transparent: bool,
+    // light、dark 或 auto，auto 时跟随查看者的深色模式
+    pub theme: ASvgTheme,
+    pub dark_stroke_color: String,
+    pub dark_fill_color: String,
+    pub dark_background: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct APngConfig {
+    // 缩放比例
+    pub scale: f32,
+    // 背景色，为空时保留 svg 的白色背景
+    pub background: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct APdfConfig {
+    // fit、a4、a3 或 letter
+    pub page: APageSize,
+    // 页边距，单位毫米
+    pub margin: f32,
+}
+
+// asciibox.toml 中的配置，命令行和图形界面共用，保证团队中渲染结果一致
+// 未出现的字段使用默认值
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AConfig {
+    pub render: ARenderConfig,
+    pub table: ATableConfig,
+    pub doc: ADocConfig,
+    pub watch: AWatchConfig,
+    pub svg: ASvgConfig,
+    pub png: APngConfig,
+    pub pdf: APdfConfig,
+    // 配置文件所在路径，使用默认配置时为空
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for ARenderConfig {
+    fn default() -> Self {
+        let options = DiagramOptions::default();
+        Self {
+            expand: options.expand_mode,
+            charset: options.charset,
+            padding: options.padding,
+            direction: options.direction,
+        }
+    }
+}
+
+impl Default for ATableConfig {
+    fn default() -> Self {
+        Self {
+            max_width: TableFormator::new().max_w,
+        }
+    }
+}
+
+impl Default for ADocConfig {
+    fn default() -> Self {
+        Self {
+            svg: false,
+            format_tables: true,
+        }
+    }
+}
+
+impl Default for AWatchConfig {
+    fn default() -> Self {
+        Self { debounce: 300 }
+    }
+}
+
+impl Default for ASvgConfig {
+    fn default() -> Self {
+        let options = ASvgOptions::default();
+        Self {
+            font_family: options.font_family,
+            font_size: options.font_size,
+            stroke_width: options.stroke_width,
+            stroke_color: options.stroke_color,
+            fill_color: options.fill_color,
+            background: options.background,
+            scale: options.scale,
+            transparent: options.transparent,
+            theme: options.theme,
+            dark_stroke_color: options.dark_stroke_color,
+            dark_fill_color: options.dark_fill_color,
+            dark_background: options.dark_background,
+        }
+    }
+}
+
+impl Default for APngConfig {
+    fn default() -> Self {
+        let options = APngOptions::default();
+        Self {
+            scale: options.scale,
+            background: options.background,
+        }
+    }
+}
+
+impl Default for APdfConfig {
+    fn default() -> Self {
+        let options = APdfOptions::default();
+        Self {
+            page: options.page,
+            margin: options.margin,
+        }
+    }
+}
+
+impl AConfig {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| e.message().to_string())?;
+        if config.render.padding == 0 {
+            return Err("render.padding should be at least 1".to_string());
+        }
+        let svg = &config.svg;
+        if svg.font_size == 0 || !(svg.stroke_width > 0.0 && svg.scale > 0.0) {
+            return Err(
+                "svg.font_size, svg.stroke_width and svg.scale should be positive".to_string(),
+            );
+        }
+        if !config.png.scale.is_finite() || config.png.scale <= 0.0 {
+            return Err("png.scale should be greater than 0".to_string());
+        }
+        if !config.pdf.margin.is_finite() || config.pdf.margin < 0.0 {
+            return Err("pdf.margin should not be negative".to_string());
+        }
+        if let Some(color) = config.png.background.as_deref() {
+            if parse_color(color).is_none() {
+                return Err(format!("png.background: invalid color '{}'", color));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut config = Self::parse(content.as_str())
+            .map_err(|e| format!("{}: {}", path.display(), e.trim_end()))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    // 从 dir 开始逐级向上查找配置文件
+    pub fn discover(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|d| d.join(CONFIG_NAME))
+            .find(|p| p.is_file())
+    }
+
+    // 找不到配置文件时使用默认配置
+    pub fn load_from(dir: &Path) -> Result<Self, String> {
+        match Self::discover(dir) {
+            Some(path) => Self::load(path.as_path()),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn diagram_options(&self) -> DiagramOptions {
+        DiagramOptions {
+            expand_mode: self.render.expand,
+            charset: self.render.charset,
+            padding: self.render.padding,
+            direction: self.render.direction,
+            css_tags: false,
+        }
+    }
+
+    pub fn svg_options(&self) -> ASvgOptions {
+        let svg = self.svg.clone();
+        ASvgOptions {
+            font_family: svg.font_family,
+            font_size: svg.font_size,
+            stroke_width: svg.stroke_width,
+            stroke_color: svg.stroke_color,
+            fill_color: svg.fill_color,
+            background: svg.background,
+            scale: svg.scale,
+            transparent: svg.transparent,
+            theme: svg.theme,
+            dark_stroke_color: svg.dark_stroke_color,
+            dark_fill_color: svg.dark_fill_color,
+            dark_background: svg.dark_background,
+        }
+    }
+
+    pub fn png_options(&self) -> APngOptions {
+        APngOptions {
+            scale: self.png.scale,
+            background: self.png.background.clone(),
+        }
+    }
+
+    pub fn pdf_options(&self) -> APdfOptions {
+        APdfOptions {
+            page: self.pdf.page,
+            margin: self.pdf.margin,
+        }
+    }
+
+    pub fn table_formator(&self) -> TableFormator {
+        let mut formator = TableFormator::new();
+        formator.max_w = self.table.max_width;
+        formator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = AConfig::parse(
+            "[render]\nexpand = true\ncharset = \"unicode\"\ndirection = \"TD\"\n\n[table]\nmax_width = 40\n",
+        )
+        .unwrap();
+        let options = config.diagram_options();
+        assert!(options.expand_mode);
+        assert_eq!(options.charset, ACharset::Unicode);
+        assert_eq!(options.direction, ADirection::TopDown);
+        assert_eq!(options.padding, 1);
+        assert_eq!(config.table_formator().max_w, 40);
+        assert!(config.doc.format_tables);
+        assert_eq!(AConfig::parse("").unwrap(), AConfig::default());
+
+        assert!(AConfig::parse("[render]\nexpnad = true").is_err());
+        assert!(AConfig::parse("[render]\npadding = 0").is_err());
+        assert!(AConfig::parse("[render]\ncharset = \"ebcdic\"").is_err());
+        let config = AConfig::parse("[png]\nscale = 2\nbackground = \"#fff\"").unwrap();
+        assert_eq!(config.png_options().scale, 2.0);
+        assert!(AConfig::parse("[png]\nscale = 0").is_err());
+        assert!(AConfig::parse("[png]\nbackground = \"nope\"").is_err());
+        let config = AConfig::parse("[pdf]\npage = \"a4\"\nmargin = 0").unwrap();
+        assert_eq!(config.pdf_options().page, APageSize::A4);
+        assert!(AConfig::parse("[pdf]\npage = \"b5\"").is_err());
+        let config = AConfig::parse("[svg]\nbackground = \"#1e1e1e\"\nstroke_width = 1").unwrap();
+        let options = config.svg_options();
+        assert_eq!(
+            (options.background.as_str(), options.stroke_width),
+            ("#1e1e1e", 1.0)
+        );
+        assert_eq!(options.font_size, ASvgOptions::default().font_size);
+        assert!(AConfig::parse("[svg]\nfont_size = 0").is_err());
+        let config = AConfig::parse("[svg]\ntheme = \"auto\"").unwrap();
+        assert_eq!(config.svg_options().theme, ASvgTheme::Auto);
+    }
+
+    #[test]
+    fn test_discover() {
+        let root = std::env::temp_dir().join(format!("asciibox-config-{}", std::process::id()));
+        let sub = root.join("docs").join("a");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(root.join(CONFIG_NAME), "[watch]\ndebounce = 50\n").unwrap();
+        let config = AConfig::load_from(&sub).unwrap();
+        assert_eq!(config.watch.debounce, 50);
+        assert_eq!(config.path, Some(root.join(CONFIG_NAME)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
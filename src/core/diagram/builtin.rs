@@ -0,0 +1,337 @@
+use super::kind::{diagnose_lines, first_word, ADiagnostic, Diagram, DiagramKind, DiagramOptions};
+use crate::core::chart::{invalid_values, parse_axis, APie, AXyChart};
+use crate::core::gitgraph::parse_command;
+use crate::core::gitgraph::AGitGraph;
+use crate::core::import::{is_dot, ADotParser};
+use crate::core::svgbob::{parse_click, parse_step, ADirection, AMap, Arrow};
+use crate::core::timeline::{AJourney, ATimeline};
+use crate::core::tree::{ATree, ATreeMode};
+
+impl Diagram for AMap {
+    fn render(&mut self) -> String {
+        AMap::render(self)
+    }
+}
+
+impl Diagram for APie {
+    fn render(&mut self) -> String {
+        APie::render(self)
+    }
+}
+
+impl Diagram for AXyChart {
+    fn render(&mut self) -> String {
+        AXyChart::render(self)
+    }
+}
+
+impl Diagram for AGitGraph {
+    fn render(&mut self) -> String {
+        AGitGraph::render(self)
+    }
+}
+
+impl Diagram for ATimeline {
+    fn render(&mut self) -> String {
+        ATimeline::render(self)
+    }
+}
+
+impl Diagram for AJourney {
+    fn render(&mut self) -> String {
+        AJourney::render(self)
+    }
+}
+
+impl Diagram for ATree {
+    fn render(&mut self) -> String {
+        ATree::render(self)
+    }
+}
+
+// 流程图，也是无法识别类型时的默认类型
+pub struct FlowchartKind;
+
+impl DiagramKind for FlowchartKind {
+    fn name(&self) -> &str {
+        "flowchart"
+    }
+
+    fn detect(&self, first_line: &str) -> bool {
+        matches!(first_word(first_line), "graph" | "flowchart")
+    }
+
+    fn parse(&self, content: &str, options: &DiagramOptions) -> Box<dyn Diagram> {
+        Box::new(self.parse_map(content, options).unwrap())
+    }
+
+    fn parse_map(&self, content: &str, options: &DiagramOptions) -> Option<AMap> {
+        // 去掉 graph TD 这样的首行，否则会被当作节点
+        let lines: Vec<&str> = content.lines().collect();
+        let header = lines
+            .iter()
+            .position(|l| !l.trim().is_empty() && !l.trim().starts_with("%%"));
+        let body: Vec<&str> = lines
+            .iter()
+            .enumerate()
+            .filter(|(i, l)| {
+                // %% step N 用于分步显示，交给 AMap 处理
+                if l.trim().starts_with("%%") {
+                    return parse_step(l).is_some();
+                }
+                Some(*i) != header || !self.detect(l.trim())
+            })
+            .map(|(_, l)| *l)
+            .collect();
+        // 首行中的方向优先于配置
+        let direction = header
+            .filter(|&i| self.detect(lines[i].trim()))
+            .and_then(|i| lines[i].split_whitespace().nth(1))
+            .and_then(|v| ADirection::from_name(v.trim_end_matches(';')))
+            .unwrap_or(options.direction);
+        let mut map = AMap::new(options.expand_mode);
+        map.set_padding(options.padding);
+        map.set_direction(direction);
+        map.set_css_tags(options.css_tags);
+        map.parse_content(body.join("\n").as_str());
+        Some(map)
+    }
+
+    fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        diagnose_lines(content, self, |line| {
+            // svgbob 只能给封闭的图形加样式，解析时忽略连线样式
+            if first_word(line) == "linkStyle" {
+                return Some(
+                    "linkStyle is not supported, edges keep the default style".to_string(),
+                );
+            }
+            if first_word(line) == "click" {
+                let value = line
+                    .split_whitespace()
+                    .skip(2)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                return match parse_click(value.as_str()) {
+                    Some(_) => None,
+                    None => Some(
+                        "click only supports http, https, mailto and relative links".to_string(),
+                    ),
+                };
+            }
+            let open = line.chars().filter(|c| "([{".contains(*c)).count();
+            let close = line.chars().filter(|c| ")]}".contains(*c)).count();
+            if open != close {
+                return Some("unbalanced brackets in node".to_string());
+            }
+            if line.ends_with('>') || line.ends_with("--") {
+                return Some("edge without target node".to_string());
+            }
+            None
+        })
+    }
+}
+
+// graphviz dot，使用与流程图相同的布局
+pub struct DotKind;
+
+impl DiagramKind for DotKind {
+    fn name(&self) -> &str {
+        "dot"
+    }
+
+    fn detect(&self, first_line: &str) -> bool {
+        is_dot(first_line)
+    }
+
+    fn parse(&self, content: &str, options: &DiagramOptions) -> Box<dyn Diagram> {
+        Box::new(self.parse_map(content, options).unwrap())
+    }
+
+    fn parse_map(&self, content: &str, options: &DiagramOptions) -> Option<AMap> {
+        let graph = ADotParser::new().do_parse(content);
+        // 未指定 rankdir 时使用配置中的方向
+        let direction = graph.direction.unwrap_or(options.direction);
+        let edges: Vec<Arrow> = graph
+            .edges
+            .iter()
+            .map(|e| {
+                let mut edge = e.clone();
+                edge.direct = direction.turn(e.direct.clone());
+                edge
+            })
+            .collect();
+        let mut map = AMap::new(options.expand_mode);
+        map.set_padding(options.padding);
+        map.set_direction(direction);
+        map.parse_graph(&graph.cells, &edges);
+        Some(map)
+    }
+
+    fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        let mut parser = ADotParser::new();
+        parser.do_parse(content);
+        parser.errors
+    }
+}
+
+pub struct PieKind;
+
+impl DiagramKind for PieKind {
+    fn name(&self) -> &str {
+        "pie"
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut pie = APie::new();
+        pie.build_slices(content);
+        Box::new(pie)
+    }
+
+    fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        diagnose_lines(content, self, |line| {
+            if line.starts_with("title") || line.starts_with("showData") {
+                return None;
+            }
+            let value = match line.rsplit_once(':') {
+                Some(v) => v.1.trim(),
+                None => return Some("expected \"label\" : value".to_string()),
+            };
+            match value.parse::<f64>() {
+                Ok(v) if !v.is_finite() => Some(format!("invalid value '{}'", value)),
+                Ok(v) if v < 0.0 => Some(format!("negative value '{}'", value)),
+                Ok(_) => None,
+                Err(_) => Some(format!("invalid value '{}'", value)),
+            }
+        })
+    }
+}
+
+pub struct XyChartKind;
+
+impl DiagramKind for XyChartKind {
+    fn name(&self) -> &str {
+        "xychart-beta"
+    }
+
+    fn detect(&self, first_line: &str) -> bool {
+        matches!(first_word(first_line), "xychart-beta" | "xychart")
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut chart = AXyChart::new();
+        chart.build_series(content);
+        Box::new(chart)
+    }
+
+    fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        diagnose_lines(content, self, |line| {
+            let key = first_word(line);
+            match key {
+                "x-axis" | "y-axis" => match parse_axis(&line[key.len()..]).range {
+                    Some((a, b)) if a >= b => {
+                        Some(format!("range '{} --> {}' must go from low to high", a, b))
+                    }
+                    _ => None,
+                },
+                "bar" | "line" => invalid_values(&line[key.len()..])
+                    .first()
+                    .map(|v| format!("invalid value '{}'", v)),
+                "title" => None,
+                _ => Some(format!("unknown statement '{}'", key)),
+            }
+        })
+    }
+}
+
+pub struct GitGraphKind;
+
+impl DiagramKind for GitGraphKind {
+    fn name(&self) -> &str {
+        "gitGraph"
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut graph = AGitGraph::new();
+        graph.build_commits(content);
+        Box::new(graph)
+    }
+
+    fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        diagnose_lines(content, self, |line| match parse_command(line) {
+            Some(_) => None,
+            None => Some(format!("unknown command '{}'", first_word(line))),
+        })
+    }
+}
+
+pub struct TimelineKind;
+
+impl DiagramKind for TimelineKind {
+    fn name(&self) -> &str {
+        "timeline"
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut timeline = ATimeline::new();
+        timeline.build_periods(content);
+        Box::new(timeline)
+    }
+}
+
+pub struct JourneyKind;
+
+impl DiagramKind for JourneyKind {
+    fn name(&self) -> &str {
+        "journey"
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut journey = AJourney::new();
+        journey.build_tasks(content);
+        Box::new(journey)
+    }
+
+    fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        diagnose_lines(content, self, |line| {
+            if line.starts_with("title ") || line.starts_with("section ") {
+                return None;
+            }
+            let score = match line.split(':').nth(1) {
+                Some(v) => v.trim(),
+                None => return Some("expected task: score: actors".to_string()),
+            };
+            match score.parse::<usize>() {
+                Ok(v) if (1..=5).contains(&v) => None,
+                _ => Some(format!("score '{}' should be 1 to 5", score)),
+            }
+        })
+    }
+}
+
+pub struct MindmapKind;
+
+impl DiagramKind for MindmapKind {
+    fn name(&self) -> &str {
+        "mindmap"
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut tree = ATree::new(ATreeMode::Mindmap);
+        tree.build_nodes(content);
+        Box::new(tree)
+    }
+}
+
+pub struct TreeKind;
+
+impl DiagramKind for TreeKind {
+    fn name(&self) -> &str {
+        "tree"
+    }
+
+    fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+        let mut tree = ATree::new(ATreeMode::Tree);
+        tree.build_nodes(content);
+        Box::new(tree)
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+// 生成文本使用的字符集
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ACharset {
+    #[default]
+    Ascii,
+    // 使用 unicode 制表符，适合直接在终端或代码注释中阅读
+    Unicode,
+}
+
+impl ACharset {
+    pub fn convert(&self, text: &str) -> String {
+        match self {
+            ACharset::Ascii => text.to_string(),
+            ACharset::Unicode => to_unicode(text),
+        }
+    }
+}
+
+// 四个方向是否有线连接，依次为上下左右
+fn corner(arms: (bool, bool, bool, bool), round: bool) -> Option<char> {
+    let c = match arms {
+        (false, true, false, true) if round => '╭',
+        (false, true, true, false) if round => '╮',
+        (true, false, false, true) if round => '╰',
+        (true, false, true, false) if round => '╯',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (true, true, true, true) => '┼',
+        (false, false, true, true) => '─',
+        (true, true, false, false) => '│',
+        _ => return None,
+    };
+    Some(c)
+}
+
+// 将 svgbob 风格的 ascii 图转换为 unicode 制表符
+// 只转换与线相连的字符，节点文字中的 - | 等保持不变
+pub fn to_unicode(text: &str) -> String {
+    let grid: Vec<Vec<char>> = text.split('\n').map(|l| l.chars().collect()).collect();
+    let at =
+        |y: usize, x: usize| -> char { grid.get(y).and_then(|l| l.get(x)).copied().unwrap_or(' ') };
+    let mut lines: Vec<String> = Vec::with_capacity(grid.len());
+    for (y, line) in grid.iter().enumerate() {
+        let mut result = String::with_capacity(line.len());
+        for (x, &c) in line.iter().enumerate() {
+            let up = match y {
+                0 => ' ',
+                _ => at(y - 1, x),
+            };
+            let down = at(y + 1, x);
+            let left = match x {
+                0 => ' ',
+                _ => at(y, x - 1),
+            };
+            let right = at(y, x + 1);
+            let vertical = |v: char| "|.'+^v".contains(v);
+            let arms = (
+                up == '|' || up == '^',
+                down == '|' || down == 'v',
+                left == '-' || left == '<',
+                right == '-' || right == '>',
+            );
+            let v = match c {
+                '-' if left == '-' || right == '-' || "<>.'+".contains(left) => '─',
+                '-' if "<>.'+".contains(right) => '─',
+                '|' if vertical(up) || vertical(down) => '│',
+                '.' | '\'' => corner(arms, true).unwrap_or(c),
+                '+' => corner(arms, false).unwrap_or(c),
+                '>' if left == '-' => '▶',
+                '<' if right == '-' => '◀',
+                'v' if up == '|' => '▼',
+                '^' if down == '|' => '▲',
+                _ => c,
+            };
+            result.push(v);
+        }
+        lines.push(result);
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_unicode() {
+        let ascii = ".---.   +-----+
+| a |-->| b-c |
+'---'   +-----+
+  |
+  v";
+        let expect = "╭───╮   ┌─────┐
+│ a │──▶│ b-c │
+╰───╯   └─────┘
+  │
+  ▼";
+        assert_eq!(to_unicode(ascii), expect);
+        assert_eq!(ACharset::Ascii.convert(ascii), ascii);
+    }
+}
@@ -0,0 +1,96 @@
+use super::charset::ACharset;
+use crate::core::svgbob::{ADirection, AMap};
+
+// 生成 ascii 图时的公共选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramOptions {
+    // 是否扩展 box 保证同一列宽度相同
+    pub expand_mode: bool,
+    pub charset: ACharset,
+    // 流程图 box 内文字两侧的空白
+    pub padding: usize,
+    // 流程图首行未指定方向时使用
+    pub direction: ADirection,
+    // 流程图中有样式的节点写入 svgbob 的 css 标签和图例，只在生成 svg 时开启
+    pub css_tags: bool,
+}
+
+impl Default for DiagramOptions {
+    fn default() -> Self {
+        Self {
+            expand_mode: false,
+            charset: ACharset::Ascii,
+            padding: 1,
+            direction: ADirection::LeftRight,
+            css_tags: false,
+        }
+    }
+}
+
+// 解析时发现的问题，line 为在输入内容中的行号(从 0 开始)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ADiagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+// 解析之后的图，负责生成 svgbob 文本
+pub trait Diagram {
+    fn render(&mut self) -> String;
+}
+
+// 一种图的类型，例如 flowchart、pie、gitGraph
+// 通过首行关键字识别，新类型只需要实现本 trait 并注册到 DiagramRegistry
+pub trait DiagramKind: Send + Sync {
+    // 类型名称，同时也是默认的首行关键字
+    fn name(&self) -> &str;
+
+    // 判断首行(已去掉首尾空白及注释行)是否属于该类型
+    fn detect(&self, first_line: &str) -> bool {
+        first_word(first_line) == self.name()
+    }
+
+    // 解析输入内容，content 包含首行
+    fn parse(&self, content: &str, options: &DiagramOptions) -> Box<dyn Diagram>;
+
+    // 由节点和连线组成的图返回解析后的 AMap，用于导出为其他格式
+    fn parse_map(&self, _content: &str, _options: &DiagramOptions) -> Option<AMap> {
+        None
+    }
+
+    // 检查无法解析的行，解析本身对错误是宽松的，这里只用于编辑器提示
+    fn diagnose(&self, _content: &str) -> Vec<ADiagnostic> {
+        Vec::new()
+    }
+}
+
+// 逐行检查，跳过空行、注释及首行关键字所在的行
+pub fn diagnose_lines<F>(content: &str, kind: &dyn DiagramKind, check: F) -> Vec<ADiagnostic>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut header = true;
+    let mut result: Vec<ADiagnostic> = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("%%") {
+            continue;
+        }
+        if header {
+            header = false;
+            if kind.detect(line) {
+                continue;
+            }
+        }
+        if let Some(message) = check(line) {
+            result.push(ADiagnostic { line: i, message });
+        }
+    }
+    result
+}
+
+// 首行的第一个单词，去掉 mermaid 中方向等后缀，例如 gitGraph: / gitGraph TB:
+pub fn first_word(line: &str) -> &str {
+    let word = line.split_whitespace().next().unwrap_or("");
+    word.trim_end_matches(':')
+}
@@ -0,0 +1,12 @@
+mod builtin;
+mod charset;
+mod kind;
+mod registry;
+
+pub use builtin::{
+    DotKind, FlowchartKind, GitGraphKind, JourneyKind, MindmapKind, PieKind, TimelineKind,
+    TreeKind, XyChartKind,
+};
+pub use charset::{to_unicode, ACharset};
+pub use kind::{diagnose_lines, first_word, ADiagnostic, Diagram, DiagramKind, DiagramOptions};
+pub use registry::{diagnose, first_line, parse_map, register_kind, render, DiagramRegistry};
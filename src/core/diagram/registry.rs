@@ -0,0 +1,230 @@
+use super::builtin::{
+    DotKind, FlowchartKind, GitGraphKind, JourneyKind, MindmapKind, PieKind, TimelineKind,
+    TreeKind, XyChartKind,
+};
+use super::kind::{ADiagnostic, Diagram, DiagramKind, DiagramOptions};
+use crate::core::svgbob::AMap;
+use std::sync::{OnceLock, RwLock};
+
+// 图类型注册表，根据首行自动识别类型
+// 识别不到时使用 fallback，即流程图
+pub struct DiagramRegistry {
+    kinds: Vec<Box<dyn DiagramKind>>,
+    fallback: Box<dyn DiagramKind>,
+}
+
+// 首个非空且不是 %% 注释的行
+pub fn first_line(content: &str) -> &str {
+    content
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty() && !l.starts_with("%%"))
+        .unwrap_or("")
+}
+
+impl DiagramRegistry {
+    // 空的注册表，只有流程图作为 fallback
+    pub fn new() -> Self {
+        Self {
+            kinds: Vec::new(),
+            fallback: Box::new(FlowchartKind),
+        }
+    }
+
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(FlowchartKind));
+        registry.register(Box::new(PieKind));
+        registry.register(Box::new(XyChartKind));
+        registry.register(Box::new(GitGraphKind));
+        registry.register(Box::new(TimelineKind));
+        registry.register(Box::new(JourneyKind));
+        registry.register(Box::new(MindmapKind));
+        registry.register(Box::new(TreeKind));
+        registry.register(Box::new(DotKind));
+        registry
+    }
+
+    // 后注册的类型优先识别，便于覆盖内置类型
+    pub fn register(&mut self, kind: Box<dyn DiagramKind>) {
+        self.kinds.insert(0, kind);
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.kinds.iter().map(|k| k.name()).collect()
+    }
+
+    pub fn detect(&self, content: &str) -> &dyn DiagramKind {
+        let line = first_line(content);
+        match self.kinds.iter().find(|k| k.detect(line)) {
+            Some(kind) => kind.as_ref(),
+            None => self.fallback.as_ref(),
+        }
+    }
+
+    pub fn parse(&self, content: &str, options: &DiagramOptions) -> Box<dyn Diagram> {
+        self.detect(content).parse(content, options)
+    }
+
+    // 只有流程图这类由节点和连线组成的图可以导出
+    pub fn parse_map(&self, content: &str, options: &DiagramOptions) -> Result<AMap, String> {
+        let kind = self.detect(content);
+        match kind.parse_map(content, options) {
+            Some(v) => Ok(v),
+            None => Err(format!("{} is not a graph of nodes and edges", kind.name())),
+        }
+    }
+
+    pub fn render(&self, content: &str, options: &DiagramOptions) -> String {
+        let text = self.parse(content, options).render();
+        // 带 css 标签的结果只交给 svgbob，保持 ascii 以免图例中的样式被转换
+        match options.css_tags {
+            true => text,
+            false => options.charset.convert(text.as_str()),
+        }
+    }
+
+    // 检查无法解析的行，有内容但渲染结果为空时同样提示
+    pub fn diagnose(&self, content: &str) -> Vec<ADiagnostic> {
+        let kind = self.detect(content);
+        let mut result = kind.diagnose(content);
+        let body = content
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with("%%"))
+            .count();
+        if result.is_empty() && body > 1 {
+            let text = self.render(content, &DiagramOptions::default());
+            if text.trim().is_empty() {
+                let line = content.lines().position(|l| !l.trim().is_empty());
+                result.push(ADiagnostic {
+                    line: line.unwrap_or(0),
+                    message: format!("{} renders nothing", kind.name()),
+                });
+            }
+        }
+        result
+    }
+}
+
+impl Default for DiagramRegistry {
+    fn default() -> Self {
+        Self::with_builtin()
+    }
+}
+
+// 全局注册表，第三方类型在启动时注册一次即可，无需修改界面代码
+fn global() -> &'static RwLock<DiagramRegistry> {
+    static REGISTRY: OnceLock<RwLock<DiagramRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(DiagramRegistry::with_builtin()))
+}
+
+pub fn register_kind(kind: Box<dyn DiagramKind>) {
+    global().write().unwrap().register(kind);
+}
+
+pub fn render(content: &str, options: &DiagramOptions) -> String {
+    global().read().unwrap().render(content, options)
+}
+
+pub fn parse_map(content: &str, options: &DiagramOptions) -> Result<AMap, String> {
+    global().read().unwrap().parse_map(content, options)
+}
+
+pub fn diagnose(content: &str) -> Vec<ADiagnostic> {
+    global().read().unwrap().diagnose(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::svgbob::ADirection;
+
+    struct AUpper {
+        text: String,
+    }
+
+    impl Diagram for AUpper {
+        fn render(&mut self) -> String {
+            self.text.to_uppercase()
+        }
+    }
+
+    struct UpperKind;
+
+    impl DiagramKind for UpperKind {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        fn parse(&self, content: &str, _options: &DiagramOptions) -> Box<dyn Diagram> {
+            let text = content.lines().skip(1).collect::<Vec<_>>().join("\n");
+            Box::new(AUpper { text })
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let registry = DiagramRegistry::default();
+        let cases = [
+            ("pie title Pets\n\"Dogs\" : 3", "pie"),
+            ("%% comment\n\n  gitGraph:\ncommit", "gitGraph"),
+            ("xychart\nbar [1, 2]", "xychart-beta"),
+            ("graph TD\na --> b", "flowchart"),
+            ("a --> b", "flowchart"),
+            ("tree\na\n  b", "tree"),
+            ("digraph G {\na -> b\n}", "dot"),
+        ];
+        for (content, name) in cases {
+            assert_eq!(registry.detect(content).name(), name);
+        }
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let registry = DiagramRegistry::default();
+        let lines = |content: &str| -> Vec<usize> {
+            registry.diagnose(content).iter().map(|d| d.line).collect()
+        };
+        assert_eq!(lines("pie\n\"a\" : 1\nb : x\n\"c\" 3"), vec![2, 3]);
+        assert_eq!(
+            lines("pie\n\"a\" : NaN\n\"b\" : -inf\n\"c\" : 1"),
+            vec![1, 2]
+        );
+        assert_eq!(lines("gitGraph\ncommit\npush origin"), vec![2]);
+        assert_eq!(lines("graph TD\na[x --> b\nc -->"), vec![1, 2]);
+        assert_eq!(lines("graph TD\na --> b\nlinkStyle 0 stroke:red"), vec![2]);
+        let click = "graph TD\na --> b\nclick a \"javascript://%0aalert(1)\" \"x\"\nclick b \"https://a.b\"";
+        assert_eq!(lines(click), vec![2]);
+        assert_eq!(lines("journey\nsection s\ntask: 9: me"), vec![2]);
+        assert_eq!(lines("xychart\ntitle t"), vec![0]);
+        assert_eq!(lines("xychart\ny-axis \"v\" 100 --> 0\nbar [1]"), vec![1]);
+        assert_eq!(
+            lines("xychart\nbar [1, NaN]\nline \"s\" [inf, 2]\nbar [3]"),
+            vec![1, 2]
+        );
+        assert_eq!(lines("digraph {\na -> b\nc -- d\n}"), vec![2]);
+        assert!(registry.diagnose("a --> b").is_empty());
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = DiagramRegistry::default();
+        let options = DiagramOptions::default();
+        assert_eq!(registry.detect("upper\nabc").name(), "flowchart");
+        registry.register(Box::new(UpperKind));
+        assert_eq!(registry.render("upper\nabc", &options), "ABC");
+        assert_eq!(
+            registry.render("digraph {\n  a -> b [label=x]\n}", &options),
+            registry.render("a --> b", &options)
+        );
+        // 去掉首行后与直接解析结果一致
+        assert_eq!(
+            registry.render("graph LR\na --> b", &options),
+            registry.render("a --> b", &options)
+        );
+        let map = registry.parse_map("graph TD\na --> b", &options).unwrap();
+        assert_eq!(map.direction(), ADirection::TopDown);
+        assert!(registry.parse_map("pie\n\"a\" : 1", &options).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+// 文档格式，决定代码块的写法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ADocFormat {
+    // ```mermaid 围栏代码块
+    Markdown,
+    // [mermaid] 加 ---- 或 .... 分隔的代码块
+    Asciidoc,
+}
+
+impl ADocFormat {
+    // 根据扩展名判断，默认为 markdown
+    pub fn from_path(path: &str) -> Self {
+        let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
+        match ext.as_str() {
+            "adoc" | "asciidoc" | "asc" => ADocFormat::Asciidoc,
+            _ => ADocFormat::Markdown,
+        }
+    }
+}
+
+// 文档中的一个代码块，[start, end] 为行号，包含属性行和首尾分隔行
+#[derive(Debug, Clone, PartialEq)]
+pub struct ABlock {
+    pub lang: String,
+    pub start: usize,
+    pub end: usize,
+    // 内容首行的行号
+    pub body_start: usize,
+    // 块所在的缩进
+    pub indent: String,
+    // 去掉缩进后的块内容
+    pub body: String,
+}
+
+// 由同一字符组成的分隔行，返回字符和长度
+fn delimiter(line: &str, chars: &str, min: usize) -> Option<(char, usize)> {
+    let c = line.chars().next()?;
+    if !chars.contains(c) {
+        return None;
+    }
+    let n = line.chars().take_while(|&v| v == c).count();
+    if n < min {
+        return None;
+    }
+    Some((c, n))
+}
+
+fn leading_indent(line: &str) -> String {
+    line.chars().take_while(|c| c.is_whitespace()).collect()
+}
+
+fn block_body(lines: &[&str], from: usize, to: usize, indent: &str) -> String {
+    let body: Vec<&str> = lines[from..to]
+        .iter()
+        .map(|l| l.strip_prefix(indent).unwrap_or(l))
+        .collect();
+    body.join("\n")
+}
+
+fn find_markdown_blocks(lines: &[&str]) -> Vec<ABlock> {
+    let mut blocks: Vec<ABlock> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_start();
+        let (c, n) = match delimiter(line, "`~", 3) {
+            Some(v) => v,
+            None => {
+                i += 1;
+                continue;
+            }
+        };
+        let info: String = line.chars().skip(n).collect();
+        let lang = info.split_whitespace().next().unwrap_or("");
+        let lang = lang.trim_matches(|c| c == '{' || c == '}');
+        // 结束围栏至少与开始围栏一样长
+        let close = (i + 1..lines.len()).find(|&k| {
+            let l = lines[k].trim();
+            matches!(delimiter(l, "`~", n), Some((v, m)) if v == c && m == l.chars().count())
+        });
+        let end = match close {
+            Some(v) => v,
+            None => break,
+        };
+        let indent = leading_indent(lines[i]);
+        blocks.push(ABlock {
+            lang: lang.to_string(),
+            start: i,
+            end,
+            body_start: i + 1,
+            body: block_body(lines, i + 1, end, indent.as_str()),
+            indent,
+        });
+        i = end + 1;
+    }
+    blocks
+}
+
+// [mermaid] / [source,mermaid] / [mermaid, id, svg]
+fn asciidoc_lang(line: &str) -> Option<String> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let attrs: Vec<&str> = inner.split(',').map(|s| s.trim()).collect();
+    match attrs.first() {
+        Some(&"source") | Some(&"listing") => attrs.get(1).map(|s| s.to_string()),
+        Some(v) => Some(v.to_string()),
+        None => None,
+    }
+}
+
+fn find_asciidoc_blocks(lines: &[&str]) -> Vec<ABlock> {
+    let mut blocks: Vec<ABlock> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        if delimiter(line, "-.", 4).map(|(_, n)| n) != Some(line.len()) {
+            i += 1;
+            continue;
+        }
+        let close = (i + 1..lines.len()).find(|&k| lines[k].trim_end() == line);
+        let end = match close {
+            Some(v) => v,
+            None => break,
+        };
+        // 没有属性行的块语言为空，同样需要记录，避免匹配到块内部的内容
+        let attr = match i {
+            0 => None,
+            _ => asciidoc_lang(lines[i - 1]),
+        };
+        let start = match attr {
+            Some(_) => i - 1,
+            None => i,
+        };
+        blocks.push(ABlock {
+            lang: attr.unwrap_or_default(),
+            start,
+            end,
+            body_start: i + 1,
+            indent: String::new(),
+            body: block_body(lines, i + 1, end, ""),
+        });
+        i = end + 1;
+    }
+    blocks
+}
+
+pub fn find_blocks(lines: &[&str], format: ADocFormat) -> Vec<ABlock> {
+    match format {
+        ADocFormat::Markdown => find_markdown_blocks(lines),
+        ADocFormat::Asciidoc => find_asciidoc_blocks(lines),
+    }
+}
+
+// 生成代码块时使用的分隔符，比内容中同字符的分隔行更长
+pub fn fence_for(c: char, min: usize, content: &str) -> String {
+    let longest = content
+        .lines()
+        .filter_map(|l| delimiter(l.trim(), c.to_string().as_str(), 1))
+        .map(|(_, n)| n)
+        .max()
+        .unwrap_or(0);
+    c.to_string().repeat(std::cmp::max(min, longest + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_blocks() {
+        let md = "# t\n````mermaid\na --> b\n```\n````\n  ~~~ svgbob\n  x\n  ~~~\n```\nopen";
+        let lines: Vec<&str> = md.split('\n').collect();
+        let blocks = find_blocks(&lines, ADocFormat::Markdown);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].body, "a --> b\n```");
+        assert_eq!((blocks[1].lang.as_str(), blocks[1].end), ("svgbob", 7));
+        assert_eq!(blocks[1].body, "x");
+
+        let adoc = "[source,mermaid]\n----\na --> b\n----\n\n....\n[mermaid]\n....\n";
+        let lines: Vec<&str> = adoc.split('\n').collect();
+        let blocks = find_blocks(&lines, ADocFormat::Asciidoc);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!((blocks[0].start, blocks[0].end), (0, 3));
+        assert_eq!((blocks[1].lang.as_str(), blocks[1].start), ("", 5));
+        assert_eq!(fence_for('.', 4, "x\n......\n"), ".......");
+    }
+}
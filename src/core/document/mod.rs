@@ -0,0 +1,6 @@
+mod block;
+mod processor;
+mod table;
+
+pub use block::{find_blocks, ABlock, ADocFormat};
+pub use processor::{ADocProcessor, AEdit, AEmbedMode, AFile};
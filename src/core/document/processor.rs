@@ -0,0 +1,311 @@
+use super::block::{fence_for, find_blocks, ABlock, ADocFormat};
+use super::table::{find_tables, format_table};
+use crate::core::adoc::TableFormator;
+use crate::core::diagram::{self, DiagramOptions};
+use crate::core::export::{ascii_to_svg, ASvgOptions};
+
+// mermaid 块的输出方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AEmbedMode {
+    // 在其后插入 svgbob 代码块
+    Svgbob,
+    // 生成 svg 文件并在其后插入图片引用
+    SvgFile,
+}
+
+// 需要写入磁盘的生成文件
+#[derive(Debug, Clone, PartialEq)]
+pub struct AFile {
+    pub path: String,
+    pub content: String,
+}
+
+// 对文档的一处修改，行号从 0 开始
+#[derive(Debug, Clone, PartialEq)]
+pub struct AEdit {
+    // 对应 mermaid 块的首行
+    pub block: usize,
+    pub start: usize,
+    pub end: usize,
+    pub lines: Vec<String>,
+}
+
+// 处理 markdown/asciidoc 文档，在每个 mermaid 块之后插入或刷新渲染结果
+// 其余内容保持不变，重复处理结果一致
+#[derive(Debug, Clone)]
+pub struct ADocProcessor {
+    pub format: ADocFormat,
+    pub mode: AEmbedMode,
+    pub options: DiagramOptions,
+    // 生成 svg 文件时的样式
+    pub svg_options: ASvgOptions,
+    // svg 文件名前缀，生成 <prefix>-<n>.svg
+    pub svg_prefix: String,
+    // 是否同时格式化 asciidoc 表格
+    pub format_tables: bool,
+    // 表格分隔行的最大宽度
+    pub table_width: usize,
+    // 最近一次处理生成的 svg 文件
+    pub files: Vec<AFile>,
+}
+
+// 图片引用的替代文本，用于识别由本工具生成的引用
+const IMAGE_ALT: &str = "mermaid";
+
+impl ADocProcessor {
+    pub fn new(format: ADocFormat) -> Self {
+        Self {
+            format,
+            mode: AEmbedMode::Svgbob,
+            options: DiagramOptions::default(),
+            svg_options: ASvgOptions::default(),
+            svg_prefix: "diagram".to_string(),
+            format_tables: true,
+            table_width: TableFormator::new().max_w,
+            files: Vec::new(),
+        }
+    }
+
+    fn is_image_ref(&self, line: &str) -> bool {
+        let line = line.trim();
+        match self.format {
+            ADocFormat::Markdown => {
+                line.starts_with(format!("![{}](", IMAGE_ALT).as_str()) && line.ends_with(')')
+            }
+            ADocFormat::Asciidoc => {
+                line.starts_with("image::") && line.ends_with(format!("[{}]", IMAGE_ALT).as_str())
+            }
+        }
+    }
+
+    fn svgbob_block(&self, block: &ABlock, ascii: &str) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        match self.format {
+            ADocFormat::Markdown => {
+                let fence = fence_for('`', 3, ascii);
+                lines.push(format!("{}svgbob", fence));
+                lines.extend(ascii.lines().map(|l| l.to_string()));
+                lines.push(fence);
+            }
+            ADocFormat::Asciidoc => {
+                let fence = fence_for('.', 4, ascii);
+                lines.push("[svgbob]".to_string());
+                lines.push(fence.clone());
+                lines.extend(ascii.lines().map(|l| l.to_string()));
+                lines.push(fence);
+            }
+        }
+        lines
+            .into_iter()
+            .map(|l| match l.is_empty() {
+                true => l,
+                false => format!("{}{}", block.indent, l),
+            })
+            .collect()
+    }
+
+    fn image_ref(&self, block: &ABlock, path: &str) -> Vec<String> {
+        let line = match self.format {
+            ADocFormat::Markdown => format!("![{}]({})", IMAGE_ALT, path),
+            ADocFormat::Asciidoc => format!("image::{}[{}]", path, IMAGE_ALT),
+        };
+        vec![format!("{}{}", block.indent, line)]
+    }
+
+    // 第 n 个 mermaid 块的渲染结果
+    fn render_block(&mut self, block: &ABlock, n: usize) -> Vec<String> {
+        let ascii = diagram::render(block.body.as_str(), &self.options);
+        if ascii.trim().is_empty() {
+            return Vec::new();
+        }
+        match self.mode {
+            AEmbedMode::Svgbob => self.svgbob_block(block, ascii.as_str()),
+            AEmbedMode::SvgFile => {
+                let path = format!("{}-{}.svg", self.svg_prefix, n);
+                // svg 中保留节点的颜色
+                let options = DiagramOptions {
+                    css_tags: true,
+                    ..self.options.clone()
+                };
+                let tagged = diagram::render(block.body.as_str(), &options);
+                self.files.push(AFile {
+                    path: path.clone(),
+                    content: ascii_to_svg(tagged.as_str(), &self.svg_options),
+                });
+                self.image_ref(block, path.as_str())
+            }
+        }
+    }
+
+    // 使用 TableFormator 格式化文档中的表格
+    pub fn process_tables(&self, input: &str) -> String {
+        let lines: Vec<&str> = input.split('\n').collect();
+        let blocks = find_blocks(&lines, self.format);
+        let mut content: Vec<String> = Vec::new();
+        let mut pos = 0;
+        for table in find_tables(&lines, &blocks) {
+            if let Some(v) = format_table(&lines[table.start..=table.end], self.table_width) {
+                content.extend(lines[pos..table.start].iter().map(|l| l.to_string()));
+                content.extend(v);
+                pos = table.end + 1;
+            }
+        }
+        content.extend(lines[pos..].iter().map(|l| l.to_string()));
+        content.join("\n")
+    }
+
+    pub fn do_process(&mut self, input: &str) -> String {
+        let content = self.process_diagrams(input);
+        match self.format == ADocFormat::Asciidoc && self.format_tables {
+            true => self.process_tables(content.as_str()),
+            false => content,
+        }
+    }
+
+    // 每个 mermaid 块对应一个修改，替换其后的 [start, end) 行，start == end 时为插入
+    pub fn diagram_edits(&mut self, input: &str) -> Vec<AEdit> {
+        self.files.clear();
+        let lines: Vec<&str> = input.split('\n').collect();
+        let blocks = find_blocks(&lines, self.format);
+        let mut edits: Vec<AEdit> = Vec::new();
+        let mut n = 0;
+        let mut i = 0;
+        while i < blocks.len() {
+            let block = &blocks[i];
+            i += 1;
+            if block.lang != "mermaid" {
+                continue;
+            }
+            n += 1;
+            let start = block.end + 1;
+            let mut end = start;
+
+            // 跳过已有的生成结果，中间只允许有空行
+            let blank =
+                |from: usize, to: usize| lines[from..to].iter().all(|l| l.trim().is_empty());
+            let next = (start..lines.len()).find(|&k| !lines[k].trim().is_empty());
+            match blocks.get(i) {
+                Some(v) if v.lang == "svgbob" && blank(start, v.start) => {
+                    end = v.end + 1;
+                    i += 1;
+                }
+                _ => {
+                    if let Some(k) = next.filter(|&k| self.is_image_ref(lines[k])) {
+                        end = k + 1;
+                    }
+                }
+            }
+
+            let mut generated = self.render_block(block, n);
+            if !generated.is_empty() {
+                generated.insert(0, String::new());
+            }
+            edits.push(AEdit {
+                block: block.start,
+                start,
+                end,
+                lines: generated,
+            });
+        }
+        edits
+    }
+
+    fn process_diagrams(&mut self, input: &str) -> String {
+        let lines: Vec<&str> = input.split('\n').collect();
+        let mut content: Vec<String> = Vec::new();
+        let mut pos = 0;
+        for edit in self.diagram_edits(input) {
+            content.extend(lines[pos..edit.start].iter().map(|l| l.to_string()));
+            content.extend(edit.lines);
+            pos = edit.end;
+        }
+        content.extend(lines[pos..].iter().map(|l| l.to_string()));
+        content.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_process() {
+        let doc = "# title\n\n```mermaid\na --> b\n```\n\ntext\n\n```rust\nfn main() {}\n```\n";
+        let mut processor = ADocProcessor::new(ADocFormat::Markdown);
+        let result = processor.do_process(doc);
+        let expect = "# title
+
+```mermaid
+a --> b
+```
+
+```svgbob
+.---.   .---.
+| a |-->| b |
+'---'   '---'
+```
+
+text
+
+```rust
+fn main() {}
+```
+";
+        assert_eq!(result, expect);
+        assert_eq!(processor.do_process(result.as_str()), expect);
+
+        // 源码修改后刷新已有的结果
+        let changed = result.replace("a --> b\n```", "a --> c\n```");
+        assert!(processor
+            .do_process(changed.as_str())
+            .contains("| a |-->| c |"));
+    }
+
+    #[test]
+    fn test_asciidoc_svg_file() {
+        let doc = "== t\n\n[mermaid]\n----\na --> b\n----\n\n|===\n| a\n|=======";
+        let mut processor = ADocProcessor::new(ADocFormat::Asciidoc);
+        processor.mode = AEmbedMode::SvgFile;
+        processor.svg_prefix = "images/t".to_string();
+        let result = processor.do_process(doc);
+        assert_eq!(
+            result,
+            "== t\n\n[mermaid]\n----\na --> b\n----\n\nimage::images/t-1.svg[mermaid]\n\n|===\n| a\n|==="
+        );
+        assert_eq!(processor.files.len(), 1);
+        assert_eq!(processor.do_process(result.as_str()), result);
+        assert!(processor.files[0].content.starts_with("<svg"));
+
+        // 切换为 svgbob 时替换已有的图片引用
+        processor.mode = AEmbedMode::Svgbob;
+        let result = processor.do_process(result.as_str());
+        assert!(result.contains("----\n\n[svgbob]\n....\n.---."));
+        assert!(!result.contains("image::"));
+        assert_eq!(processor.do_process(result.as_str()), result);
+    }
+
+    #[test]
+    fn test_asciidoc_tables() {
+        let doc =
+            "|===\n|a|bb\n\n|ccc|d\n|===\n\n----\n|===\n|x|y\n|===\n----\n\n|===\n2+|merged\n|===";
+        let mut processor = ADocProcessor::new(ADocFormat::Asciidoc);
+        let result = processor.do_process(doc);
+        let expect = "|=========
+| a   | bb
+
+| ccc | d
+|=========
+
+----
+|===
+|x|y
+|===
+----
+
+|===
+2+|merged
+|===";
+        assert_eq!(result, expect);
+        assert_eq!(processor.do_process(result.as_str()), expect);
+    }
+}
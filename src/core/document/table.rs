@@ -0,0 +1,59 @@
+use super::block::ABlock;
+use crate::core::adoc::TableFormator;
+
+// asciidoc 表格，[start, end] 为首尾 |=== 所在行号
+#[derive(Debug, Clone, PartialEq)]
+pub struct ATable {
+    pub start: usize,
+    pub end: usize,
+}
+
+fn is_border(line: &str) -> bool {
+    match line.trim_end().strip_prefix("|=") {
+        Some(v) => v.chars().all(|c| c == '='),
+        None => false,
+    }
+}
+
+// 查找代码块之外的表格
+pub fn find_tables(lines: &[&str], blocks: &[ABlock]) -> Vec<ATable> {
+    let mut tables: Vec<ATable> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if let Some(b) = blocks.iter().find(|b| b.start <= i && i <= b.end) {
+            i = b.end + 1;
+            continue;
+        }
+        if !is_border(lines[i]) {
+            i += 1;
+            continue;
+        }
+        let end = match (i + 1..lines.len()).find(|&k| is_border(lines[k])) {
+            Some(v) => v,
+            None => break,
+        };
+        tables.push(ATable { start: i, end });
+        i = end + 1;
+    }
+    tables
+}
+
+// 只格式化每行都以 | 开头的简单表格，包含合并单元格、单元格样式或转义的表格保持不变
+// max_w 为分隔行的最大宽度
+pub fn format_table(lines: &[&str], max_w: usize) -> Option<Vec<String>> {
+    let simple = lines
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .all(|l| l.starts_with('|') && !l.contains("\\|"));
+    if !simple {
+        return None;
+    }
+    let mut formator = TableFormator::new();
+    formator.max_w = max_w;
+    let result = formator.do_format(lines.join("\n").as_str());
+    if result.is_empty() {
+        return None;
+    }
+    Some(result.lines().map(|l| l.to_string()).collect())
+}
@@ -0,0 +1,91 @@
+use crate::core::svgbob::{ADirection, AMap, ASharp, Direct};
+
+// dot 中的字符串，统一加引号
+fn quote(text: &str) -> String {
+    let text = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", text)
+}
+
+// 导出为 graphviz dot，dot 没有单条连线的方向，上下箭头按普通连线处理
+pub fn to_dot(map: &AMap) -> String {
+    let direction = map.direction();
+    let mut content = String::from("digraph G {\n");
+    match direction {
+        ADirection::LeftRight => content.push_str("    rankdir=LR;\n"),
+        ADirection::TopDown => content.push_str("    rankdir=TB;\n"),
+    }
+    for cell in map.cells() {
+        let shape = match cell.sharp() {
+            ASharp::Round => "ellipse",
+            ASharp::Square => "box",
+            ASharp::Circle => "circle",
+        };
+        let mut attrs = format!("label={}, shape={}", quote(cell.name.as_str()), shape);
+        if let Some(link) = cell.link.as_ref() {
+            attrs.push_str(format!(", URL={}", quote(link.url.as_str())).as_str());
+            if !link.tooltip.is_empty() {
+                attrs.push_str(format!(", tooltip={}", quote(link.tooltip.as_str())).as_str());
+            }
+            if let Some(target) = link.target.as_ref() {
+                attrs.push_str(format!(", target={}", quote(target)).as_str());
+            }
+        }
+        content.push_str(format!("    {} [{}];\n", quote(cell.id.as_str()), attrs).as_str());
+    }
+    for edge in map.edges() {
+        let mut attrs: Vec<String> = Vec::new();
+        if !edge.text.is_empty() {
+            attrs.push(format!("label={}", quote(edge.text.as_str())));
+        }
+        match direction.turn(edge.direct.clone()) {
+            Direct::Left | Direct::LeftUp | Direct::LeftDown => attrs.push("dir=back".to_string()),
+            Direct::Double => attrs.push("dir=both".to_string()),
+            Direct::None => attrs.push("dir=none".to_string()),
+            _ => {}
+        }
+        let attrs = match attrs.is_empty() {
+            true => String::new(),
+            false => format!(" [{}]", attrs.join(", ")),
+        };
+        content.push_str(
+            format!(
+                "    {} -> {}{};\n",
+                quote(edge.src.as_str()),
+                quote(edge.dst.as_str()),
+                attrs
+            )
+            .as_str(),
+        );
+    }
+    content.push_str("}\n");
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::diagram::{DiagramOptions, DiagramRegistry};
+
+    #[test]
+    fn test_to_dot() {
+        let registry = DiagramRegistry::default();
+        let options = DiagramOptions::default();
+        let source = "a[Say \"hi\"] --|x|--> b\nb <-- c\nc <--> d";
+        let map = registry.parse_map(source, &options).unwrap();
+        let result = to_dot(&map);
+        assert!(result.contains("    \"a\" [label=\"Say \\\"hi\\\"\", shape=box];\n"));
+        assert!(result.contains("    \"a\" -> \"b\" [label=\"x\"];\n"));
+        assert!(result.contains("    \"b\" -> \"c\" [dir=back];\n"));
+        assert!(result.contains("    \"c\" -> \"d\" [dir=both];\n"));
+        // 导出的 dot 可以再次导入，布局与原始输入一致
+        let source = "a[A] --> b\nb --> c(C)";
+        let map = registry.parse_map(source, &options).unwrap();
+        assert_eq!(
+            registry.render(to_dot(&map).as_str(), &options),
+            registry.render(source, &options)
+        );
+    }
+}
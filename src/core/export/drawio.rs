@@ -0,0 +1,91 @@
+use crate::core::svgbob::{AMap, ASharp};
+use crate::core::utils::escape;
+
+use super::json::AJsonGraph;
+use super::layout::{arrow_heads, node_bounds};
+
+// draw.io 的属性值中换行写为 &#xa;
+fn value(text: &str) -> String {
+    escape(text).replace('\n', "&#xa;")
+}
+
+// 导出为 draw.io 的 mxGraph xml，节点位置与 ascii 输出相同，连线由 draw.io 重新布线
+pub fn to_drawio(map: &AMap) -> String {
+    let graph = AJsonGraph::new(map);
+    let bounds = node_bounds(&graph);
+    let mut content = String::from(
+        "<mxfile host=\"asciibox\">
+  <diagram id=\"asciibox\" name=\"Page-1\">
+    <mxGraphModel grid=\"1\" gridSize=\"8\" arrows=\"1\" connect=\"1\">
+      <root>
+        <mxCell id=\"0\" />
+        <mxCell id=\"1\" parent=\"0\" />
+",
+    );
+    for (cell, (x, y, w, h)) in map.cells().into_iter().zip(bounds) {
+        let shape = match cell.sharp() {
+            ASharp::Round => "rounded=1;",
+            ASharp::Square => "rounded=0;",
+            ASharp::Circle => "ellipse;",
+        };
+        content.push_str(
+            format!(
+                "        <mxCell id=\"node-{}\" value=\"{}\" style=\"{}whiteSpace=wrap;\" vertex=\"1\" parent=\"1\">
+          <mxGeometry x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" as=\"geometry\" />
+        </mxCell>
+",
+                value(cell.id.as_str()),
+                value(cell.name.as_str()),
+                shape,
+                x,
+                y,
+                w,
+                h
+            )
+            .as_str(),
+        );
+    }
+    for (i, edge) in map.edges().iter().enumerate() {
+        let arrow = |head: bool| if head { "classic" } else { "none" };
+        let (start, end) = arrow_heads(map.direction(), edge);
+        content.push_str(
+            format!(
+                "        <mxCell id=\"edge-{}\" value=\"{}\" style=\"edgeStyle=orthogonalEdgeStyle;rounded=0;startArrow={};endArrow={};\" edge=\"1\" parent=\"1\" source=\"node-{}\" target=\"node-{}\">
+          <mxGeometry relative=\"1\" as=\"geometry\" />
+        </mxCell>
+",
+                i,
+                value(edge.text.as_str()),
+                arrow(start),
+                arrow(end),
+                value(edge.src.as_str()),
+                value(edge.dst.as_str())
+            )
+            .as_str(),
+        );
+    }
+    content.push_str("      </root>\n    </mxGraphModel>\n  </diagram>\n</mxfile>\n");
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_drawio() {
+        let mut map = AMap::new(false);
+        map.parse_content("a[Start] --> b(\"x & y\")\nb --|ok|--> c{Done}\nb <-- d");
+        let xml = to_drawio(&map);
+        assert!(xml.starts_with("<mxfile host=\"asciibox\">"));
+        assert!(xml.contains(
+            "<mxCell id=\"node-a\" value=\"Start\" style=\"rounded=0;whiteSpace=wrap;\" vertex=\"1\" parent=\"1\">\n          <mxGeometry x=\"0\" y=\"0\" width=\"72\" height=\"48\" as=\"geometry\" />"
+        ));
+        assert!(xml.contains("value=\"&quot;x &amp; y&quot;\" style=\"rounded=1;"));
+        assert!(xml.contains("style=\"ellipse;whiteSpace=wrap;\""));
+        assert!(xml.contains("<mxCell id=\"edge-1\" value=\"ok\""));
+        assert!(xml.contains("startArrow=classic;endArrow=none;\" edge=\"1\" parent=\"1\" source=\"node-b\" target=\"node-d\""));
+        assert_eq!(xml.matches("vertex=\"1\"").count(), 4);
+        assert_eq!(xml.matches("edge=\"1\"").count(), 3);
+    }
+}
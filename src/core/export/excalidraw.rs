@@ -0,0 +1,209 @@
+use serde_json::{json, Value};
+
+use crate::core::svgbob::{AMap, ASharp};
+
+use super::json::AJsonGraph;
+use super::layout::{arrow_heads, connect, node_bounds, ABounds};
+
+const STROKE_COLOR: &str = "#1e1e1e";
+const FONT_SIZE: f64 = 16.0;
+const LINE_HEIGHT: f64 = 1.25;
+
+// 所有元素共有的字段，seed 固定以保证每次导出的结果相同
+fn element(id: &str, kind: &str, bounds: ABounds, seed: usize) -> Value {
+    let (x, y, width, height) = bounds;
+    json!({
+        "id": id,
+        "type": kind,
+        "x": x,
+        "y": y,
+        "width": width,
+        "height": height,
+        "angle": 0,
+        "strokeColor": STROKE_COLOR,
+        "backgroundColor": "transparent",
+        "fillStyle": "solid",
+        "strokeWidth": 2,
+        "strokeStyle": "solid",
+        "roughness": 0,
+        "opacity": 100,
+        "groupIds": [],
+        "frameId": null,
+        "roundness": null,
+        "seed": seed,
+        "version": 1,
+        "versionNonce": seed,
+        "isDeleted": false,
+        "boundElements": [],
+        "updated": 1,
+        "link": null,
+        "locked": false
+    })
+}
+
+// 放在节点或连线中的文字，位置由 excalidraw 根据所在元素重新计算
+fn label(id: &str, container: &str, text: &str, center: (f64, f64), seed: usize) -> Value {
+    let lines = text.lines().count().max(1) as f64;
+    let width = text.lines().map(|v| v.chars().count()).max().unwrap_or(0) as f64 * FONT_SIZE * 0.6;
+    let height = lines * FONT_SIZE * LINE_HEIGHT;
+    let bounds = (
+        center.0 - width / 2.0,
+        center.1 - height / 2.0,
+        width,
+        height,
+    );
+    let mut value = element(id, "text", bounds, seed);
+    let fields = json!({
+        "text": text,
+        "originalText": text,
+        "fontSize": FONT_SIZE,
+        // 3 为等宽字体
+        "fontFamily": 3,
+        "textAlign": "center",
+        "verticalAlign": "middle",
+        "containerId": container,
+        "lineHeight": LINE_HEIGHT,
+        "autoResize": true
+    });
+    value
+        .as_object_mut()
+        .unwrap()
+        .extend(fields.as_object().unwrap().clone());
+    value
+}
+
+fn bind(value: &mut Value, id: &str, kind: &str) {
+    value["boundElements"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"id": id, "type": kind}));
+}
+
+// 导出为 excalidraw 的 json，节点位置与 ascii 输出相同，连线绑定到两端的节点
+pub fn to_excalidraw(map: &AMap) -> String {
+    let graph = AJsonGraph::new(map);
+    let bounds = node_bounds(&graph);
+    let cells = map.cells();
+    let index = |id: &str| cells.iter().position(|c| c.id == id);
+    let mut shapes: Vec<Value> = Vec::new();
+    let mut texts: Vec<Value> = Vec::new();
+    for (i, (cell, b)) in cells.iter().zip(bounds.iter()).enumerate() {
+        let id = format!("node-{}", cell.id);
+        let (kind, roundness) = match cell.sharp() {
+            ASharp::Round => ("rectangle", json!({"type": 3})),
+            ASharp::Square => ("rectangle", Value::Null),
+            ASharp::Circle => ("ellipse", json!({"type": 2})),
+        };
+        let mut shape = element(id.as_str(), kind, *b, i * 2 + 1);
+        shape["roundness"] = roundness;
+        if let Some(link) = cell.link.as_ref() {
+            shape["link"] = json!(link.url);
+        }
+        let text_id = format!("{}-label", id);
+        let center = (b.0 + b.2 / 2.0, b.1 + b.3 / 2.0);
+        bind(&mut shape, text_id.as_str(), "text");
+        texts.push(label(
+            text_id.as_str(),
+            id.as_str(),
+            cell.name.as_str(),
+            center,
+            i * 2 + 2,
+        ));
+        shapes.push(shape);
+    }
+    let mut arrows: Vec<Value> = Vec::new();
+    for (i, edge) in map.edges().iter().enumerate() {
+        let (src, dst) = match (index(edge.src.as_str()), index(edge.dst.as_str())) {
+            (Some(s), Some(d)) => (s, d),
+            _ => continue,
+        };
+        let id = format!("edge-{}", i);
+        let seed = cells.len() * 2 + i * 2 + 1;
+        let ((x1, y1), (x2, y2)) = connect(bounds[src], bounds[dst]);
+        let area = (x1, y1, (x2 - x1).abs(), (y2 - y1).abs());
+        let mut arrow = element(id.as_str(), "arrow", area, seed);
+        let (start, end) = arrow_heads(map.direction(), edge);
+        let head = |v: bool| if v { json!("arrow") } else { Value::Null };
+        let fields = json!({
+            "points": [[0, 0], [x2 - x1, y2 - y1]],
+            "lastCommittedPoint": null,
+            "startBinding": {"elementId": format!("node-{}", edge.src), "focus": 0, "gap": 1},
+            "endBinding": {"elementId": format!("node-{}", edge.dst), "focus": 0, "gap": 1},
+            "startArrowhead": head(start),
+            "endArrowhead": head(end)
+        });
+        arrow
+            .as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        bind(&mut shapes[src], id.as_str(), "arrow");
+        bind(&mut shapes[dst], id.as_str(), "arrow");
+        if !edge.text.is_empty() {
+            let text_id = format!("{}-label", id);
+            let center = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+            bind(&mut arrow, text_id.as_str(), "text");
+            texts.push(label(
+                text_id.as_str(),
+                id.as_str(),
+                edge.text.as_str(),
+                center,
+                seed + 1,
+            ));
+        }
+        arrows.push(arrow);
+    }
+    let elements: Vec<Value> = shapes.into_iter().chain(arrows).chain(texts).collect();
+    let document = json!({
+        "type": "excalidraw",
+        "version": 2,
+        "source": "asciibox",
+        "elements": elements,
+        "appState": {"viewBackgroundColor": "#ffffff", "gridSize": null},
+        "files": {}
+    });
+    let mut content = serde_json::to_string_pretty(&document).unwrap();
+    content.push('\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_excalidraw() {
+        let mut map = AMap::new(false);
+        map.parse_content(
+            "a[Start] --> b(Next)\nb --|ok|--> c{Done}\nclick c \"https://example.com\"",
+        );
+        let value: Value = serde_json::from_str(to_excalidraw(&map).as_str()).unwrap();
+        assert_eq!(value["type"], "excalidraw");
+        let elements = value["elements"].as_array().unwrap();
+        let find = |id: &str| elements.iter().find(|e| e["id"] == id).unwrap();
+        let a = find("node-a");
+        assert_eq!(
+            (a["type"].as_str(), a["x"].as_f64()),
+            (Some("rectangle"), Some(0.0))
+        );
+        assert_eq!(
+            (a["width"].as_f64(), a["height"].as_f64()),
+            (Some(72.0), Some(48.0))
+        );
+        assert!(a["roundness"].is_null());
+        assert_eq!(find("node-c")["type"], "ellipse");
+        assert_eq!(find("node-c")["link"], "https://example.com");
+        assert_eq!(find("node-a-label")["text"], "Start");
+        assert_eq!(find("node-a-label")["containerId"], "node-a");
+        let edge = find("edge-0");
+        assert_eq!(edge["startBinding"]["elementId"], "node-a");
+        assert_eq!(edge["endBinding"]["elementId"], "node-b");
+        assert_eq!(
+            (edge["x"].as_f64(), edge["endArrowhead"].as_str()),
+            (Some(72.0), Some("arrow"))
+        );
+        assert!(edge["startArrowhead"].is_null());
+        assert_eq!(find("edge-1-label")["text"], "ok");
+        // 节点记录绑定在其上的文字和连线
+        assert_eq!(find("node-b")["boundElements"].as_array().unwrap().len(), 3);
+    }
+}
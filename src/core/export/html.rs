@@ -0,0 +1,108 @@
+use crate::core::utils::escape;
+
+// 生成 html 时的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct AHtmlOptions {
+    // 只输出 <figure> 片段，用于插入其他页面
+    pub snippet: bool,
+    // 完整页面的标题
+    pub title: String,
+}
+
+impl Default for AHtmlOptions {
+    fn default() -> Self {
+        Self {
+            snippet: false,
+            title: "Diagram".to_string(),
+        }
+    }
+}
+
+// 完整页面的样式，片段中不包含，由所在页面决定
+const PAGE_STYLE: &str = "figure.asciibox { margin: 1em; }
+figure.asciibox svg { max-width: 100%; height: auto; }
+figure.asciibox pre { font-family: monospace; line-height: 1.2; overflow-x: auto; }
+figure.asciibox summary { cursor: pointer; }
+";
+
+// 生成不依赖外部资源的 html：内嵌 svg，ascii 图放在 <pre> 中可以选中复制，原始输入放在折叠块中
+// source 为空时没有原始输入的折叠块
+pub fn to_html(svg: &str, ascii: &str, source: &str, options: &AHtmlOptions) -> String {
+    // 内嵌时不需要 xml 声明
+    let svg = match svg.find("<svg") {
+        Some(i) => &svg[i..],
+        None => svg,
+    };
+    let mut content = String::from("<figure class=\"asciibox\">\n");
+    content
+        .push_str(format!("<div class=\"asciibox-svg\">\n{}\n</div>\n", svg.trim_end()).as_str());
+    content.push_str(
+        format!(
+            "<details class=\"asciibox-ascii\">\n<summary>ASCII</summary>\n<pre>{}</pre>\n</details>\n",
+            escape(ascii.trim_end_matches('\n'))
+        )
+        .as_str(),
+    );
+    if !source.trim().is_empty() {
+        content.push_str(
+            format!(
+                "<details class=\"asciibox-source\">\n<summary>Source</summary>\n<pre><code>{}</code></pre>\n</details>\n",
+                escape(source.trim_end_matches('\n'))
+            )
+            .as_str(),
+        );
+    }
+    content.push_str("</figure>\n");
+    if options.snippet {
+        return content;
+    }
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<meta name=\"generator\" content=\"asciibox\">
+<title>{}</title>
+<style>
+{}</style>
+</head>
+<body>
+{}</body>
+</html>
+",
+        escape(options.title.as_str()),
+        PAGE_STYLE,
+        content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::export::{ascii_to_svg, ASvgOptions};
+
+    #[test]
+    fn test_to_html() {
+        let ascii = ".---.\n| a |\n'---'\n";
+        let svg = ascii_to_svg(ascii, &ASvgOptions::default());
+        let source = "graph TD\na --> b & c";
+        let html = to_html(svg.as_str(), ascii, source, &AHtmlOptions::default());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Diagram</title>"));
+        assert!(html.contains(svg.trim_end()));
+        assert!(html.contains("<pre>.---.\n| a |\n'---'</pre>"));
+        assert!(html.contains("<pre><code>graph TD\na --&gt; b &amp; c</code></pre>"));
+        // 不引用外部资源
+        assert!(!html.contains("src=") && !html.contains("<link"));
+
+        let options = AHtmlOptions {
+            snippet: true,
+            ..AHtmlOptions::default()
+        };
+        let snippet = to_html(svg.as_str(), ascii, "", &options);
+        assert!(snippet.starts_with("<figure class=\"asciibox\">"));
+        assert!(snippet.ends_with("</figure>\n"));
+        assert!(!snippet.contains("<style>\nfigure") && !snippet.contains("asciibox-source"));
+    }
+}
@@ -0,0 +1,168 @@
+use serde::Serialize;
+
+use crate::core::import::AAsciiReader;
+use crate::core::svgbob::{ADirection, ALink, AMap, ASharp};
+
+// 导出的 json 结构，version 变化表示字段不兼容
+//
+// {
+//   "version": 1,
+//   "direction": "LR" | "TD",
+//   "nodes": [{
+//     "id", "label", "shape": "round" | "square" | "circle",
+//     "graph": 互相连通的节点组序号,
+//     "row", "column": 在节点组中的行列,
+//     "x", "y", "width", "height": 在 ascii 输出中的字符位置，包含边框，无法确定时省略
+//     "link": {"url", "tooltip", "target"}，没有 click 语句时省略
+//   }],
+//   "edges": [{
+//     "from", "to", "label",
+//     "direction": 绘制时箭头的方向 "right" | "left" | "up" | "down" | "double" | ...
+//   }]
+// }
+pub const JSON_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AJsonNode {
+    pub id: String,
+    pub label: String,
+    pub shape: String,
+    pub graph: usize,
+    pub row: usize,
+    pub column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<ALink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AJsonEdge {
+    pub from: String,
+    pub to: String,
+    pub direction: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AJsonGraph {
+    pub version: u32,
+    pub direction: ADirection,
+    pub nodes: Vec<AJsonNode>,
+    pub edges: Vec<AJsonEdge>,
+}
+
+impl AJsonGraph {
+    pub fn new(map: &AMap) -> Self {
+        let mut nodes: Vec<AJsonNode> = Vec::new();
+        for cell in map.cells() {
+            let (graph, column, row) = map.position(cell.id.as_str()).unwrap_or_default();
+            let shape = match cell.sharp() {
+                ASharp::Round => "round",
+                ASharp::Square => "square",
+                ASharp::Circle => "circle",
+            };
+            nodes.push(AJsonNode {
+                id: cell.id.clone(),
+                label: cell.name.clone(),
+                shape: shape.to_string(),
+                graph,
+                row,
+                column,
+                x: None,
+                y: None,
+                width: None,
+                height: None,
+                link: cell.link.clone(),
+            });
+        }
+        let edges = map
+            .edges()
+            .iter()
+            .map(|e| AJsonEdge {
+                from: e.src.clone(),
+                to: e.dst.clone(),
+                direction: e.direct.to_string(),
+                label: e.text.clone(),
+            })
+            .collect();
+        let mut graph = Self {
+            version: JSON_VERSION,
+            direction: map.direction(),
+            nodes,
+            edges,
+        };
+        graph.fill_positions(map.render().as_str());
+        graph
+    }
+
+    // 从渲染结果中识别 box，同一行的 box 顶边对齐，按位置排序后与节点一一对应
+    fn fill_positions(&mut self, ascii: &str) {
+        // 忽略 svgbob 的图例
+        let ascii = &ascii[..ascii.find("# Legend:").unwrap_or(ascii.len())];
+        let mut reader = AAsciiReader::new();
+        reader.do_read(ascii);
+        if reader.boxes.len() != self.nodes.len() {
+            return;
+        }
+        let mut boxes = reader.boxes.clone();
+        boxes.sort_by_key(|b| (b.top, b.left));
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|&i| {
+            let node = &self.nodes[i];
+            (node.graph, node.row, node.column)
+        });
+        for (i, abox) in order.into_iter().zip(boxes.iter()) {
+            let node = &mut self.nodes[i];
+            node.x = Some(abox.left);
+            node.y = Some(abox.top);
+            node.width = Some(abox.right - abox.left + 1);
+            node.height = Some(abox.bottom - abox.top + 1);
+        }
+    }
+}
+
+pub fn to_json(map: &AMap) -> String {
+    let mut content = serde_json::to_string_pretty(&AJsonGraph::new(map)).unwrap();
+    content.push('\n');
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut map = AMap::new(false);
+        map.parse_content("a[Start] --> b\nb --|ok|--v c\nx");
+        let graph = AJsonGraph::new(&map);
+        let ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "x"]);
+        let a = &graph.nodes[0];
+        assert_eq!(
+            (a.shape.as_str(), a.graph, a.row, a.column),
+            ("square", 0, 0, 0)
+        );
+        assert_eq!(
+            (a.x, a.y, a.width, a.height),
+            (Some(0), Some(0), Some(9), Some(3))
+        );
+        let c = &graph.nodes[2];
+        assert_eq!((c.row, c.column), (1, 1));
+        assert_eq!(graph.nodes[3].graph, 1);
+        assert_eq!(graph.edges[1].direction, "down");
+        assert_eq!(graph.edges[1].label, "ok");
+
+        let value: serde_json::Value = serde_json::from_str(to_json(&map).as_str()).unwrap();
+        assert_eq!(value["version"], 1);
+        assert_eq!(value["direction"], "LR");
+        assert_eq!(value["nodes"][1]["label"], "b");
+    }
+}
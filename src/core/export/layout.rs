@@ -0,0 +1,75 @@
+use crate::core::svgbob::{ADirection, Arrow, Direct};
+use crate::core::utils::cn_length;
+
+use super::json::AJsonGraph;
+
+// 与 svgbob 默认比例相同，一个字符单元宽 8 像素、高 16 像素
+pub(super) const CELL_WIDTH: f64 = 8.0;
+pub(super) const CELL_HEIGHT: f64 = 16.0;
+
+// 节点的像素范围 (x, y, 宽, 高)
+pub(super) type ABounds = (f64, f64, f64, f64);
+
+// 可编辑图形格式中节点的位置，与 ascii 输出中 box 的位置相同
+// 无法从 ascii 中识别 box 时按节点组的行列排列
+pub(super) fn node_bounds(graph: &AJsonGraph) -> Vec<ABounds> {
+    // 每个节点组占用的行数，后面的节点组放在下方
+    let mut offsets: Vec<usize> = Vec::new();
+    for node in graph.nodes.iter() {
+        if offsets.len() <= node.graph {
+            offsets.resize(node.graph + 1, 0);
+        }
+        offsets[node.graph] = offsets[node.graph].max(node.row + 1);
+    }
+    let mut top = 0;
+    for rows in offsets.iter_mut() {
+        (*rows, top) = (top, top + *rows);
+    }
+    graph
+        .nodes
+        .iter()
+        .map(|node| match (node.x, node.y, node.width, node.height) {
+            (Some(x), Some(y), Some(w), Some(h)) => (
+                x as f64 * CELL_WIDTH,
+                y as f64 * CELL_HEIGHT,
+                w as f64 * CELL_WIDTH,
+                h as f64 * CELL_HEIGHT,
+            ),
+            _ => {
+                let width = node.label.lines().map(cn_length).max().unwrap_or(0) + 4;
+                let height = node.label.lines().count().max(1) + 2;
+                (
+                    (node.column * 20) as f64 * CELL_WIDTH,
+                    ((offsets[node.graph] + node.row) * 6) as f64 * CELL_HEIGHT,
+                    width as f64 * CELL_WIDTH,
+                    height as f64 * CELL_HEIGHT,
+                )
+            }
+        })
+        .collect()
+}
+
+// 连线两端是否有箭头，与 dot 导出相同，上下箭头按普通连线处理
+pub(super) fn arrow_heads(direction: ADirection, edge: &Arrow) -> (bool, bool) {
+    match direction.turn(edge.direct.clone()) {
+        Direct::Left | Direct::LeftUp | Direct::LeftDown => (true, false),
+        Direct::Double => (true, true),
+        Direct::None => (false, false),
+        _ => (false, true),
+    }
+}
+
+// 两个节点之间连线的起点和终点，位于相对的两条边的中点
+pub(super) fn connect(src: ABounds, dst: ABounds) -> ((f64, f64), (f64, f64)) {
+    let center = |b: ABounds| (b.0 + b.2 / 2.0, b.1 + b.3 / 2.0);
+    let (sx, sy) = center(src);
+    let (dx, dy) = center(dst);
+    // 水平距离较大时从左右两边连接，否则从上下两边连接
+    if (dx - sx).abs() / CELL_WIDTH >= (dy - sy).abs() / CELL_HEIGHT {
+        let sign = if dx >= sx { 1.0 } else { -1.0 };
+        ((sx + sign * src.2 / 2.0, sy), (dx - sign * dst.2 / 2.0, dy))
+    } else {
+        let sign = if dy >= sy { 1.0 } else { -1.0 };
+        ((sx, sy + sign * src.3 / 2.0), (dx, dy - sign * dst.3 / 2.0))
+    }
+}
@@ -0,0 +1,165 @@
+use crate::core::svgbob::ALink;
+use crate::core::utils::escape;
+
+use super::tags::element_points;
+
+// mermaid 中 click 语句的链接写在图例之后的 # Links: 中，svgbob 忽略这部分
+// 带链接的节点有 l0 这样的样式类，这里把节点范围内的图形和文字放入 <a> 中
+pub(super) fn apply_links(ascii: &str, svg: &str) -> String {
+    let links = parse_links(ascii);
+    if links.is_empty() {
+        return svg.to_string();
+    }
+    // 只处理背景之后的图形，跳过 <style> 和 <defs>
+    let start = match svg.find("<rect class=\"backdrop\"") {
+        Some(v) => v,
+        None => svg.find("</defs>").unwrap_or(0),
+    };
+    let (head, body) = svg.split_at(start);
+    let mut lines: Vec<&str> = body.split('\n').collect();
+    let mut anchors = String::new();
+    for (class, link) in links.iter() {
+        let bounds = match lines.iter().find_map(|line| rect_bounds(line, class)) {
+            Some(v) => v,
+            None => continue,
+        };
+        let (inside, others): (Vec<&str>, Vec<&str>) = lines
+            .into_iter()
+            .partition(|line| is_inside(line.trim_start(), bounds));
+        lines = others;
+        anchors.push_str(anchor(link, inside.as_slice()).as_str());
+    }
+    let body = lines.join("\n");
+    match body.rfind("</svg>") {
+        Some(i) => format!("{}{}{}{}", head, &body[..i], anchors, &body[i..]),
+        None => format!("{}{}", head, body),
+    }
+}
+
+// # Links: 之后每行为 l0 = {json}
+fn parse_links(ascii: &str) -> Vec<(String, ALink)> {
+    let block = match ascii.find("# Links:") {
+        Some(v) => &ascii[v..],
+        None => return Vec::new(),
+    };
+    block
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let (class, json) = line.split_once('=')?;
+            let link: ALink = serde_json::from_str(json.trim()).ok()?;
+            Some((class.trim().to_string(), link))
+        })
+        .collect()
+}
+
+fn attr<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let at = element.find(format!(" {}=\"", name).as_str())? + name.len() + 3;
+    element[at..].split('"').next()
+}
+
+fn number(element: &str, name: &str) -> Option<f32> {
+    attr(element, name)?.parse().ok()
+}
+
+// <rect> 的范围 (左, 上, 右, 下)
+fn rect_area(element: &str) -> Option<(f32, f32, f32, f32)> {
+    let (x, y) = (number(element, "x")?, number(element, "y")?);
+    Some((
+        x,
+        y,
+        x + number(element, "width")?,
+        y + number(element, "height")?,
+    ))
+}
+
+// 带有指定样式类的 <rect> 的范围
+fn rect_bounds(line: &str, class: &str) -> Option<(f32, f32, f32, f32)> {
+    let line = line.trim_start();
+    if !line.starts_with("<rect ") || !attr(line, "class")?.split(' ').any(|v| v == class) {
+        return None;
+    }
+    rect_area(line)
+}
+
+// 单行的图形或文字，所有坐标都在节点范围内
+fn is_inside(element: &str, bounds: (f32, f32, f32, f32)) -> bool {
+    let (x1, y1, x2, y2) = bounds;
+    let inside = |x: f32, y: f32| x >= x1 - 0.1 && x <= x2 + 0.1 && y >= y1 - 0.1 && y <= y2 + 0.1;
+    let points = match element.split([' ', '>']).next().unwrap_or("") {
+        "<line" | "<path" => element_points(element),
+        "<text" => match (number(element, "x"), number(element, "y")) {
+            (Some(x), Some(y)) => vec![(x, y)],
+            _ => Vec::new(),
+        },
+        "<rect" if !element.contains("class=\"backdrop\"") => match rect_area(element) {
+            Some((a, b, c, d)) => vec![(a, b), (c, d)],
+            None => Vec::new(),
+        },
+        "<circle" => match (number(element, "cx"), number(element, "cy")) {
+            (Some(x), Some(y)) => vec![(x, y)],
+            _ => Vec::new(),
+        },
+        "<polygon" => attr(element, "points")
+            .unwrap_or("")
+            .split_whitespace()
+            .filter_map(|v| {
+                let (x, y) = v.split_once(',')?;
+                Some((x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    !points.is_empty() && points.iter().all(|(x, y)| inside(*x, *y))
+}
+
+fn anchor(link: &ALink, elements: &[&str]) -> String {
+    let mut content = format!("  <a href=\"{}\"", escape(link.url.as_str()));
+    if let Some(target) = link.target.as_ref() {
+        content.push_str(format!(" target=\"{}\"", escape(target)).as_str());
+    }
+    content.push_str(">\n");
+    if !link.tooltip.is_empty() {
+        content
+            .push_str(format!("    <title>{}</title>\n", escape(link.tooltip.as_str())).as_str());
+    }
+    for element in elements {
+        content.push_str(format!("  {}\n", element).as_str());
+    }
+    content.push_str("  </a>\n");
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::diagram::{render, DiagramOptions};
+    use crate::core::export::{ascii_to_svg, ASvgOptions};
+    #[cfg(feature = "png")]
+    use crate::core::export::{svg_to_png, APngOptions};
+
+    #[test]
+    fn test_apply_links() {
+        let options = DiagramOptions {
+            css_tags: true,
+            ..DiagramOptions::default()
+        };
+        let source = "graph LR\na[Start] --> b(Next)\nclick a \"https://example.com/?a=1&b=2\" \"Go <there>\" _blank\nclick b href \"/docs\"\nclick c callback";
+        let ascii = render(source, &options);
+        assert!(ascii.contains("# Links:\nl0 = {\"url\":\"https://example.com/?a=1&b=2\""));
+        let svg = ascii_to_svg(ascii.as_str(), &ASvgOptions::default());
+        assert!(svg.contains(".svgbob .l0{ cursor: pointer; }"));
+        let a = svg
+            .find("<a href=\"https://example.com/?a=1&amp;b=2\" target=\"_blank\">")
+            .unwrap();
+        let b = svg.find("<a href=\"/docs\">").unwrap();
+        assert!(svg[a..b].contains("<title>Go &lt;there&gt;</title>"));
+        assert!(svg[a..b].contains(">Start</text>"));
+        assert!(!svg[a..b].contains(">Next</text>"));
+        assert!(svg[b..].contains(">Next</text>"));
+        // 箭头不属于任何节点
+        assert!(svg[..a].contains("class=\"solid\"></line>"));
+        assert_eq!(svg.matches("</a>").count(), 2);
+        #[cfg(feature = "png")]
+        assert!(svg_to_png(svg.as_str(), &APngOptions::default()).is_ok());
+    }
+}
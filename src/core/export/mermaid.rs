@@ -0,0 +1,107 @@
+use crate::core::svgbob::{ADirection, AMap, ASharp, AStyle, Cell, Direct};
+
+// 节点声明，与 id 相同的圆角节点直接写 id
+fn node_source(cell: &Cell) -> String {
+    let name = cell.name.replace('\n', "\\n");
+    match cell.sharp() {
+        ASharp::Round if cell.id == cell.name => cell.id.clone(),
+        ASharp::Round => format!("{}({})", cell.id, name),
+        ASharp::Square => format!("{}[{}]", cell.id, name),
+        ASharp::Circle => format!("{}{{{}}}", cell.id, name),
+    }
+}
+
+// 箭头的写法，有文字时放在两段之间，例如 --|text|-->
+fn arrow_source(direct: &Direct, text: &str) -> String {
+    let (arrow, left, right) = match direct {
+        Direct::Double => ("<-->", "<--", "-->"),
+        Direct::Left => ("<--", "<--", "--"),
+        Direct::Up => ("--^", "--", "--^"),
+        Direct::Down => ("--v", "--", "--v"),
+        Direct::LeftUp => ("<^-", "<^-", "--"),
+        Direct::LeftDown => ("<v-", "<v-", "--"),
+        Direct::RightUp => ("-^>", "-^>", "--"),
+        Direct::RightDown => ("-v>", "-v>", "--"),
+        Direct::None => ("---", "--", "---"),
+        Direct::Right => ("-->", "--", "-->"),
+    };
+    match text.is_empty() {
+        true => arrow.to_string(),
+        false => format!("{}|{}|{}", left, text.replace('\n', " "), right),
+    }
+}
+
+fn style_source(style: &AStyle) -> String {
+    let values: Vec<String> = style.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+    values.join(",")
+}
+
+// 导出为规范化的 mermaid，先逐个声明节点，再每行一条连线
+pub fn to_mermaid(map: &AMap) -> String {
+    let direction = map.direction();
+    let mut content = match direction {
+        ADirection::LeftRight => String::from("graph LR\n"),
+        ADirection::TopDown => String::from("graph TD\n"),
+    };
+    for cell in map.cells() {
+        content.push_str(format!("    {}\n", node_source(cell)).as_str());
+    }
+    for edge in map.edges() {
+        // TD 时解析阶段已经转换过方向，这里还原为书写时的方向
+        let direct = direction.turn(edge.direct.clone());
+        let arrow = arrow_source(&direct, edge.text.as_str());
+        content.push_str(format!("    {} {} {}\n", edge.src, arrow, edge.dst).as_str());
+    }
+    // a:::name 同样输出为 class 语句
+    for (name, style) in map.class_defs() {
+        content.push_str(format!("    classDef {} {}\n", name, style_source(style)).as_str());
+    }
+    for (id, class) in map.node_classes() {
+        content.push_str(format!("    class {} {}\n", id, class).as_str());
+    }
+    for (id, style) in map.node_styles() {
+        content.push_str(format!("    style {} {}\n", id, style_source(style)).as_str());
+    }
+    for cell in map.cells() {
+        if let Some(link) = cell.link.as_ref() {
+            let mut line = format!("    click {} \"{}\"", cell.id, link.url);
+            if !link.tooltip.is_empty() {
+                line.push_str(format!(" \"{}\"", link.tooltip).as_str());
+            }
+            if let Some(target) = link.target.as_ref() {
+                line.push_str(format!(" {}", target).as_str());
+            }
+            content.push_str(format!("{}\n", line).as_str());
+        }
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::diagram::{DiagramOptions, DiagramRegistry};
+
+    #[test]
+    fn test_to_mermaid() {
+        let registry = DiagramRegistry::default();
+        let options = DiagramOptions::default();
+        let source = "graph TD\na[Start]-->b{ok?}\nb --|yes|--> c(Done\\nall)\nb<--d\nx";
+        let map = registry.parse_map(source, &options).unwrap();
+        let result = to_mermaid(&map);
+        assert_eq!(
+            result,
+            "graph TD\n    a[Start]\n    b{ok?}\n    c(Done\\nall)\n    d\n    x\n    a --> b\n    b --|yes|--> c\n    b <-- d\n"
+        );
+        // 规范化之后渲染结果不变
+        assert_eq!(
+            registry.render(result.as_str(), &options),
+            registry.render(source, &options)
+        );
+        let source = "a:::hot --> b\nclassDef hot fill:#f96;\nstyle b stroke:#333,color:#fff\nclick b \"https://example.com\" \"Open\" _blank";
+        let map = registry.parse_map(source, &options).unwrap();
+        assert!(to_mermaid(&map).ends_with(
+            "    classDef hot fill:#f96\n    class a hot\n    style b stroke:#333\n    click b \"https://example.com\" \"Open\" _blank\n"
+        ));
+    }
+}
@@ -0,0 +1,82 @@
+mod dot;
+mod drawio;
+mod excalidraw;
+mod html;
+mod json;
+mod layout;
+mod links;
+mod mermaid;
+#[cfg(feature = "pdf")]
+mod pdf;
+#[cfg(feature = "png")]
+mod png;
+mod source;
+mod steps;
+mod svg;
+mod tags;
+
+use crate::core::diagram::{self, DiagramOptions};
+
+pub use dot::to_dot;
+pub use drawio::to_drawio;
+pub use excalidraw::to_excalidraw;
+pub use html::{to_html, AHtmlOptions};
+pub use json::{to_json, AJsonEdge, AJsonGraph, AJsonNode};
+pub use mermaid::to_mermaid;
+#[cfg(feature = "pdf")]
+pub use pdf::{svg_to_pdf, APageSize, APdfOptions};
+#[cfg(feature = "png")]
+pub use png::{parse_color, svg_to_png, APngOptions, BASE_DPI};
+pub use source::{embed_source, extract_source, AEmbeddedSource};
+pub use steps::{step_frames, to_animated_svg, to_step_svgs};
+pub use svg::{ascii_to_svg, ASvgOptions, ASvgTheme};
+
+// 流程图可以导出的格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AExportFormat {
+    Mermaid,
+    Dot,
+    Json,
+    Drawio,
+    Excalidraw,
+}
+
+impl AExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "mermaid" | "mmd" => Some(AExportFormat::Mermaid),
+            "dot" | "gv" | "graphviz" => Some(AExportFormat::Dot),
+            "json" => Some(AExportFormat::Json),
+            "drawio" => Some(AExportFormat::Drawio),
+            "excalidraw" => Some(AExportFormat::Excalidraw),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            AExportFormat::Mermaid => "mmd",
+            AExportFormat::Dot => "dot",
+            AExportFormat::Json => "json",
+            AExportFormat::Drawio => "drawio",
+            AExportFormat::Excalidraw => "excalidraw",
+        }
+    }
+}
+
+// 解析流程图或 dot 输入并导出为指定格式，其他类型的图返回错误
+pub fn export(
+    content: &str,
+    format: AExportFormat,
+    options: &DiagramOptions,
+) -> Result<String, String> {
+    let map = diagram::parse_map(content, options)?;
+    let result = match format {
+        AExportFormat::Mermaid => to_mermaid(&map),
+        AExportFormat::Dot => to_dot(&map),
+        AExportFormat::Json => to_json(&map),
+        AExportFormat::Drawio => to_drawio(&map),
+        AExportFormat::Excalidraw => to_excalidraw(&map),
+    };
+    Ok(result)
+}
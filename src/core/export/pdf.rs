@@ -0,0 +1,206 @@
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, TextStr};
+use resvg::usvg::{Group, Node, Tree};
+use serde::Deserialize;
+
+use super::png::{fonts, load_tree, BASE_DPI};
+
+// svg 中的 1 像素对应的 pdf 点数
+const PT_PER_PX: f32 = 72.0 / BASE_DPI;
+const PT_PER_MM: f32 = 72.0 / 25.4;
+
+// 纸张大小，Fit 时页面大小为图的大小加上页边距
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum APageSize {
+    #[default]
+    Fit,
+    A4,
+    A3,
+    Letter,
+}
+
+impl APageSize {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "fit" => Some(APageSize::Fit),
+            "a4" => Some(APageSize::A4),
+            "a3" => Some(APageSize::A3),
+            "letter" => Some(APageSize::Letter),
+            _ => None,
+        }
+    }
+
+    // 纵向时的宽高，单位为点
+    fn size(&self) -> Option<(f32, f32)> {
+        match self {
+            APageSize::Fit => None,
+            APageSize::A4 => Some((595.28, 841.89)),
+            APageSize::A3 => Some((841.89, 1190.55)),
+            APageSize::Letter => Some((612.0, 792.0)),
+        }
+    }
+}
+
+// 生成 pdf 时的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct APdfOptions {
+    pub page: APageSize,
+    // 页边距，单位毫米
+    pub margin: f32,
+}
+
+impl Default for APdfOptions {
+    fn default() -> Self {
+        Self {
+            page: APageSize::Fit,
+            margin: 10.0,
+        }
+    }
+}
+
+// 将 svgbob 生成的 svg 转换为矢量 pdf，只有一页
+// 文字按字形轮廓输出，系统中没有字体包含某个字符时返回错误，而不是输出空白或方框
+// 图比纸张大时按比例缩小，宽图自动使用横向纸张
+pub fn svg_to_pdf(svg: &str, options: &APdfOptions) -> Result<Vec<u8>, String> {
+    if !options.margin.is_finite() || options.margin < 0.0 {
+        return Err(format!("invalid margin {}", options.margin));
+    }
+    let tree = load_tree(svg)?;
+    let missing = missing_glyphs(&tree);
+    if !missing.is_empty() {
+        return Err(format!(
+            "no font contains the characters '{}', install a font that covers them",
+            missing.iter().collect::<String>()
+        ));
+    }
+    let (w, h) = (
+        tree.size.width() * PT_PER_PX,
+        tree.size.height() * PT_PER_PX,
+    );
+    let margin = options.margin * PT_PER_MM;
+    let (page_w, page_h) = match options.page.size() {
+        Some((pw, ph)) if w > h => (ph, pw),
+        Some(v) => v,
+        None => (w + margin * 2.0, h + margin * 2.0),
+    };
+    let scale = ((page_w - margin * 2.0) / w)
+        .min((page_h - margin * 2.0) / h)
+        .min(1.0);
+    if scale <= 0.0 {
+        return Err("margin is larger than the page".to_string());
+    }
+    // 水平居中，靠上放置
+    let (dw, dh) = (w * scale, h * scale);
+    let (x, y) = ((page_w - dw) / 2.0, page_h - margin - dh);
+
+    let catalog_ref = Ref::new(1);
+    let page_tree_ref = Ref::new(2);
+    let page_ref = Ref::new(3);
+    let content_ref = Ref::new(4);
+    let info_ref = Ref::new(5);
+    let svg_ref = Ref::new(6);
+    let svg_name = Name(b"D1");
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_ref).pages(page_tree_ref);
+    pdf.pages(page_tree_ref).kids([page_ref]).count(1);
+    let mut page = pdf.page(page_ref);
+    page.media_box(Rect::new(0.0, 0.0, page_w, page_h));
+    page.parent(page_tree_ref);
+    page.contents(content_ref);
+    page.resources().x_objects().pair(svg_name, svg_ref);
+    page.finish();
+    pdf.document_info(info_ref).producer(TextStr("asciibox"));
+
+    let svg_options = svg2pdf::Options {
+        dpi: BASE_DPI,
+        ..Default::default()
+    };
+    svg2pdf::convert_tree_into(&tree, svg_options, &mut pdf, svg_ref);
+    let mut content = Content::new();
+    content
+        .transform([dw, 0.0, 0.0, dh, x, y])
+        .x_object(svg_name);
+    pdf.stream(content_ref, &content.finish());
+    Ok(pdf.finish())
+}
+
+fn collect_text(group: &Group, chars: &mut Vec<char>) {
+    for node in &group.children {
+        match node {
+            Node::Group(v) => collect_text(v, chars),
+            Node::Text(v) => {
+                for chunk in &v.chunks {
+                    chars.extend(chunk.text.chars().filter(|c| !c.is_whitespace()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 找出系统中所有字体都不包含的字符，按出现顺序返回
+fn missing_glyphs(tree: &Tree) -> Vec<char> {
+    let mut chars = Vec::new();
+    collect_text(&tree.root, &mut chars);
+    let mut missing = Vec::new();
+    for c in chars {
+        if !missing.contains(&c) {
+            missing.push(c);
+        }
+    }
+    let db = fonts();
+    // 每个字体只解析一次
+    for face in db.faces() {
+        if missing.is_empty() {
+            break;
+        }
+        db.with_face_data(face.id, |data, index| {
+            if let Ok(face) = ttf_parser::Face::parse(data, index) {
+                missing.retain(|c| face.glyph_index(*c).is_none());
+            }
+        });
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgbob::to_svg;
+
+    fn media_box(pdf: &[u8]) -> String {
+        let text = String::from_utf8_lossy(pdf);
+        let begin = text.find("/MediaBox [").unwrap() + "/MediaBox [".len();
+        let end = begin + text[begin..].find(']').unwrap();
+        text[begin..end].to_string()
+    }
+
+    #[test]
+    fn test_svg_to_pdf() {
+        let svg = to_svg(".---.\n| a |\n'---'\n");
+        let wide = to_svg(".----------------.\n| a              |\n'----------------'\n");
+        let mut options = APdfOptions::default();
+        let pdf = svg_to_pdf(svg.as_str(), &options).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        options.page = APageSize::A4;
+        let pdf = svg_to_pdf(svg.as_str(), &options).unwrap();
+        assert_eq!(media_box(&pdf), "0 0 595.28 841.89");
+        let pdf = svg_to_pdf(wide.as_str(), &options).unwrap();
+        assert_eq!(media_box(&pdf), "0 0 841.89 595.28");
+        options.margin = 500.0;
+        assert!(svg_to_pdf(svg.as_str(), &options).is_err());
+        assert_eq!(APageSize::from_name("Letter"), Some(APageSize::Letter));
+        // 中文取决于系统字体：要么正确输出，要么报告缺少的字符
+        options = APdfOptions::default();
+        let cjk = to_svg(".------.\n| 中文 |\n'------'\n");
+        match svg_to_pdf(cjk.as_str(), &options) {
+            Ok(pdf) => assert!(pdf.starts_with(b"%PDF-")),
+            Err(e) => assert!(e.contains("中文"), "{}", e),
+        }
+        // 未分配的码位没有任何字体包含
+        let unknown = to_svg(".---.\n| \u{378} |\n'---'\n");
+        let e = svg_to_pdf(unknown.as_str(), &options).unwrap_err();
+        assert!(e.contains('\u{378}'), "{}", e);
+    }
+}
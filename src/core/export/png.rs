@@ -0,0 +1,179 @@
+use resvg::tiny_skia::{Color, Pixmap, Transform};
+use resvg::usvg::{fontdb, PostProcessingSteps, Tree, TreeParsing, TreePostProc};
+use std::sync::OnceLock;
+
+// svg 中 1 像素对应 96 dpi
+pub const BASE_DPI: f32 = 96.0;
+
+// 生成 png 时的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct APngOptions {
+    // 缩放比例，2.0 适合高分屏
+    pub scale: f32,
+    // 背景色，例如 white、#1e1e1e，transparent 表示透明，为空时保留 svg 自身的背景
+    pub background: Option<String>,
+}
+
+impl Default for APngOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            background: None,
+        }
+    }
+}
+
+impl APngOptions {
+    pub fn set_dpi(&mut self, dpi: f32) {
+        self.scale = dpi / BASE_DPI;
+    }
+}
+
+// 系统字体只加载一次，没有显示设备时同样可用
+pub(super) fn fonts() -> &'static fontdb::Database {
+    static FONTS: OnceLock<fontdb::Database> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        // fontdb 默认的等宽字体为 Courier New，不存在时改用系统中任意等宽字体
+        let family = db
+            .faces()
+            .filter(|f| f.monospaced)
+            .find_map(|f| f.families.first().map(|v| v.0.clone()));
+        let exists = db
+            .faces()
+            .any(|f| f.families.iter().any(|v| v.0 == "Courier New"));
+        if let (Some(family), false) = (family, exists) {
+            db.set_monospace_family(family);
+        }
+        db
+    })
+}
+
+fn hex(text: &str) -> Option<u8> {
+    // from_str_radix 会接受开头的 + 号
+    if !text.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match text.len() {
+        1 => u8::from_str_radix(text, 16).ok().map(|v| v * 17),
+        _ => u8::from_str_radix(text, 16).ok(),
+    }
+}
+
+// 支持 #rgb、#rrggbb、#rrggbbaa 及常用颜色名
+pub fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim().to_lowercase();
+    let rgba = match text.as_str() {
+        "transparent" | "none" => (0, 0, 0, 0),
+        "white" => (255, 255, 255, 255),
+        "black" => (0, 0, 0, 255),
+        "gray" | "grey" => (128, 128, 128, 255),
+        "red" => (255, 0, 0, 255),
+        "green" => (0, 128, 0, 255),
+        "blue" => (0, 0, 255, 255),
+        v if v.starts_with('#') && v.is_ascii() => {
+            let v = &v[1..];
+            let (step, alpha) = match v.len() {
+                3 => (1, 255),
+                6 => (2, 255),
+                8 => (2, hex(&v[6..8])?),
+                _ => return None,
+            };
+            let r = hex(&v[0..step])?;
+            let g = hex(&v[step..step * 2])?;
+            let b = hex(&v[step * 2..step * 3])?;
+            (r, g, b, alpha)
+        }
+        _ => return None,
+    };
+    Some(Color::from_rgba8(rgba.0, rgba.1, rgba.2, rgba.3))
+}
+
+// 解析 svg，文字转换为路径，png 和 pdf 共用
+pub(super) fn load_tree(svg: &str) -> Result<Tree, String> {
+    let mut tree = Tree::from_str(svg, &Default::default()).map_err(|e| e.to_string())?;
+    // resvg 不能直接绘制文字，需要先转换为路径
+    let steps = PostProcessingSteps {
+        convert_text_into_paths: true,
+    };
+    tree.postprocess(steps, fonts());
+    Ok(tree)
+}
+
+// 去掉 svgbob 铺满画布的白色背景，使用指定的背景色
+fn without_backdrop(svg: &str) -> String {
+    let begin = match svg.find("<rect class=\"backdrop\"") {
+        Some(v) => v,
+        None => return svg.to_string(),
+    };
+    match svg[begin..].find("</rect>") {
+        Some(end) => format!("{}{}", &svg[..begin], &svg[begin + end + "</rect>".len()..]),
+        None => svg.to_string(),
+    }
+}
+
+// 将 svgbob 生成的 svg 转换为 png，纯软件渲染
+pub fn svg_to_png(svg: &str, options: &APngOptions) -> Result<Vec<u8>, String> {
+    if !options.scale.is_finite() || options.scale <= 0.0 {
+        return Err(format!("invalid scale {}", options.scale));
+    }
+    let background = match options.background.as_deref() {
+        Some(v) => Some(parse_color(v).ok_or(format!("invalid color '{}'", v))?),
+        None => None,
+    };
+    let svg = match background {
+        Some(_) => without_backdrop(svg),
+        None => svg.to_string(),
+    };
+    let tree = load_tree(svg.as_str())?;
+    let size = tree.size.to_int_size().scale_by(options.scale);
+    let size = size.ok_or("image size is too large".to_string())?;
+    let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or(format!(
+        "can not create {}x{} image",
+        size.width(),
+        size.height()
+    ))?;
+    if let Some(color) = background {
+        pixmap.fill(color);
+    }
+    let transform = Transform::from_scale(options.scale, options.scale);
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgbob::to_svg;
+
+    #[test]
+    fn test_svg_to_png() {
+        let svg = to_svg(".---.\n| a |\n'---'\n");
+        let mut options = APngOptions::default();
+        options.set_dpi(192.0);
+        options.background = Some("#1e1e1e".to_string());
+        let png = svg_to_png(svg.as_str(), &options).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        let pixmap = Pixmap::decode_png(png.as_slice()).unwrap();
+        let one = svg_to_png(svg.as_str(), &APngOptions::default()).unwrap();
+        let one = Pixmap::decode_png(one.as_slice()).unwrap();
+        assert_eq!(pixmap.width(), one.width() * 2);
+        // 左上角为指定的背景色而不是 svgbob 的白色
+        let corner = pixmap.pixel(0, 0).unwrap();
+        assert_eq!((corner.red(), corner.alpha()), (0x1e, 255));
+
+        assert_eq!(parse_color("#fff"), Some(Color::WHITE));
+        assert_eq!(parse_color("transparent"), Some(Color::TRANSPARENT));
+        assert!(parse_color("#12").is_none());
+        assert!(parse_color("#ff0000zz").is_none());
+        assert!(parse_color("#+f0000").is_none());
+        assert_eq!(
+            parse_color("#ff000080"),
+            Some(Color::from_rgba8(255, 0, 0, 128))
+        );
+        assert!(!without_backdrop(svg.as_str()).contains("backdrop\""));
+        options.background = Some("nope".to_string());
+        assert!(svg_to_png(svg.as_str(), &options).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::diagram::{ACharset, DiagramOptions};
+use crate::core::svgbob::ADirection;
+use crate::core::utils::escape;
+
+const SOURCE_TAG: &str = "asciibox:source";
+const SOURCE_NS: &str = "https://github.com/nobodygx/asciibox";
+
+// 嵌入 svg <metadata> 中的原始输入和渲染选项，再次打开时恢复编辑
+// 内容为 json，version 变化表示字段不兼容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AEmbeddedSource {
+    pub version: u32,
+    // 输入框中的内容，通常为 mermaid
+    pub source: String,
+    // 生成 svg 的 ascii 图，可能经过手工修改
+    pub ascii: String,
+    pub expand_mode: bool,
+    pub charset: ACharset,
+    pub padding: usize,
+    pub direction: ADirection,
+}
+
+impl Default for AEmbeddedSource {
+    fn default() -> Self {
+        Self::new("", "", &DiagramOptions::default())
+    }
+}
+
+impl AEmbeddedSource {
+    pub fn new(source: &str, ascii: &str, options: &DiagramOptions) -> Self {
+        Self {
+            version: 1,
+            source: source.to_string(),
+            ascii: ascii.to_string(),
+            expand_mode: options.expand_mode,
+            charset: options.charset,
+            padding: options.padding,
+            direction: options.direction,
+        }
+    }
+
+    pub fn diagram_options(&self) -> DiagramOptions {
+        DiagramOptions {
+            expand_mode: self.expand_mode,
+            charset: self.charset,
+            padding: self.padding,
+            direction: self.direction,
+            ..DiagramOptions::default()
+        }
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// 在 <svg> 开始标签之后插入 <metadata>，已有的嵌入内容会被替换
+pub fn embed_source(svg: &str, source: &AEmbeddedSource) -> String {
+    let svg = without_source(svg);
+    let begin = match svg.find("<svg") {
+        Some(v) => v,
+        None => return svg,
+    };
+    let end = match svg[begin..].find('>') {
+        Some(v) => begin + v + 1,
+        None => return svg,
+    };
+    let json = serde_json::to_string(source).unwrap();
+    format!(
+        "{}\n  <metadata><{SOURCE_TAG} xmlns:asciibox=\"{SOURCE_NS}\">{}</{SOURCE_TAG}></metadata>{}",
+        &svg[..end],
+        escape(json.as_str()),
+        &svg[end..]
+    )
+}
+
+// 嵌入内容的位置，包含外层的 <metadata>
+fn source_range(svg: &str) -> Option<(usize, usize, usize, usize)> {
+    let tag = svg.find(format!("<{SOURCE_TAG}").as_str())?;
+    let text_begin = tag + svg[tag..].find('>')? + 1;
+    let text_end = text_begin + svg[text_begin..].find(format!("</{SOURCE_TAG}>").as_str())?;
+    let mut begin = tag;
+    let mut end = text_end + SOURCE_TAG.len() + 3;
+    let before = svg[..begin].trim_end();
+    let after = svg[end..].trim_start();
+    if before.ends_with("<metadata>") && after.starts_with("</metadata>") {
+        begin = before.len() - "<metadata>".len();
+        end = svg.len() - after.len() + "</metadata>".len();
+    }
+    Some((begin, text_begin, text_end, end))
+}
+
+fn without_source(svg: &str) -> String {
+    match source_range(svg) {
+        Some((begin, _, _, end)) => {
+            format!("{}{}", svg[..begin].trim_end(), &svg[end..])
+        }
+        None => svg.to_string(),
+    }
+}
+
+// 读取 asciibox 保存的 svg 中嵌入的内容，其他 svg 返回 None
+pub fn extract_source(svg: &str) -> Option<AEmbeddedSource> {
+    let (_, begin, end, _) = source_range(svg)?;
+    serde_json::from_str(unescape(&svg[begin..end]).as_str()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::export::{ascii_to_svg, ASvgOptions};
+    #[cfg(feature = "png")]
+    use crate::core::export::{svg_to_png, APngOptions};
+
+    #[test]
+    fn test_embed_source() {
+        let ascii = ".---.\n| a |\n'---'\n";
+        let options = DiagramOptions {
+            expand_mode: true,
+            charset: ACharset::Unicode,
+            ..DiagramOptions::default()
+        };
+        let source =
+            AEmbeddedSource::new("graph TD\na <--> b & c\n<!-- ]]> -->\n", ascii, &options);
+        let svg = ascii_to_svg(ascii, &ASvgOptions::default());
+        let embedded = embed_source(svg.as_str(), &source);
+        assert!(embedded.starts_with("<svg"));
+        assert_eq!(extract_source(embedded.as_str()), Some(source.clone()));
+        assert_eq!(
+            extract_source(embedded.as_str()).unwrap().diagram_options(),
+            options
+        );
+        // 再次嵌入时替换原有内容
+        let changed = AEmbeddedSource::new("a --> b", ascii, &options);
+        let twice = embed_source(embedded.as_str(), &changed);
+        assert_eq!(twice.matches("<metadata>").count(), 1);
+        assert_eq!(extract_source(twice.as_str()), Some(changed));
+        assert_eq!(without_source(embedded.as_str()), svg);
+        // 嵌入内容不影响渲染
+        #[cfg(feature = "png")]
+        assert!(svg_to_png(embedded.as_str(), &APngOptions::default()).is_ok());
+        assert!(extract_source(svg.as_str()).is_none());
+    }
+}
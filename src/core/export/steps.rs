@@ -0,0 +1,256 @@
+use std::collections::BTreeSet;
+
+use crate::core::svgbob::AMap;
+
+use super::json::AJsonGraph;
+use super::svg::{ascii_to_svg, ASvgOptions};
+use super::tags::to_grid;
+
+// 每一帧淡入的时长，单位秒
+const FADE_IN: f32 = 0.3;
+
+// 分步显示时每一步的 ascii 图，布局与完整的图相同，尚未出现的节点和连线替换为空格
+// 连线按相连的字符分组，共用线段的一组连线在其中第一条出现时整组显示
+pub fn step_frames(map: &AMap) -> Result<Vec<String>, String> {
+    let ascii = map.render();
+    let (body, legend) = ascii.split_at(ascii.find("# Legend:").unwrap_or(ascii.len()));
+    let grid = to_grid(body);
+    let graph = AJsonGraph::new(map);
+    // 每个字符所属的节点，连线上的字符为 None
+    let mut owners: Vec<Vec<Option<usize>>> =
+        grid.iter().map(|row| vec![None; row.len()]).collect();
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let (x, y, w, h) = match (node.x, node.y, node.width, node.height) {
+            (Some(x), Some(y), Some(w), Some(h)) => (x, y, w, h),
+            _ => return Err("can not locate boxes in the diagram".to_string()),
+        };
+        for row in owners.iter_mut().skip(y).take(h) {
+            for owner in row.iter_mut().skip(x).take(w) {
+                *owner = Some(i);
+            }
+        }
+    }
+    let index = |id: &str| graph.nodes.iter().position(|n| n.id == id);
+    let steps = map.steps();
+    if steps.is_empty() {
+        return Err("nothing to animate, the diagram has no nodes".to_string());
+    }
+    let mut node_step = vec![0; graph.nodes.len()];
+    let mut edge_step = vec![0; map.edges().len()];
+    for (k, (nodes, edges)) in steps.iter().enumerate() {
+        for i in nodes.iter().filter_map(|id| index(id)) {
+            node_step[i] = k;
+        }
+        for &i in edges {
+            edge_step[i] = k;
+        }
+    }
+    // 每个字符出现的步骤
+    let mut char_step: Vec<Vec<usize>> = owners
+        .iter()
+        .map(|row| row.iter().map(|o| o.map_or(0, |i| node_step[i])).collect())
+        .collect();
+    let mut visited: Vec<Vec<bool>> = grid.iter().map(|row| vec![false; row.len()]).collect();
+    for r in 0..grid.len() {
+        for c in 0..grid[r].len() {
+            if visited[r][c] || owners[r][c].is_some() || grid[r][c] == ' ' {
+                continue;
+            }
+            let (cells, touched) = flood(&grid, &owners, &mut visited, (r, c));
+            let joined = |id: &str| index(id).is_some_and(|i| touched.contains(&i));
+            let step = map
+                .edges()
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| joined(e.src.as_str()) && joined(e.dst.as_str()))
+                .map(|(i, _)| edge_step[i])
+                .min()
+                .or(touched.iter().map(|&i| node_step[i]).max())
+                .unwrap_or(0);
+            for (r, c) in cells {
+                char_step[r][c] = step;
+            }
+        }
+    }
+    let frames = (0..steps.len())
+        .map(|k| {
+            let lines: Vec<String> = grid
+                .iter()
+                .zip(char_step.iter())
+                .map(|(row, steps)| {
+                    // 中文占两个单元，隐藏时替换为两个空格
+                    row.iter()
+                        .zip(steps.iter())
+                        .filter_map(|(ch, step)| match (*step > k, *ch) {
+                            (true, _) => Some(' '),
+                            (false, '\0') => None,
+                            (false, ch) => Some(ch),
+                        })
+                        .collect()
+                })
+                .collect();
+            format!("{}\n{}", lines.join("\n"), legend)
+        })
+        .collect();
+    Ok(frames)
+}
+
+// 与起点相连的连线字符，以及与这些字符相邻的节点
+fn flood(
+    grid: &[Vec<char>],
+    owners: &[Vec<Option<usize>>],
+    visited: &mut [Vec<bool>],
+    start: (usize, usize),
+) -> (Vec<(usize, usize)>, BTreeSet<usize>) {
+    let mut cells = Vec::new();
+    let mut touched = BTreeSet::new();
+    let mut stack = vec![start];
+    visited[start.0][start.1] = true;
+    while let Some((r, c)) = stack.pop() {
+        cells.push((r, c));
+        for (dr, dc) in [
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+            (0, -1),
+            (0, 1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+        ] {
+            let (nr, nc) = (r as isize + dr, c as isize + dc);
+            if nr < 0 || nc < 0 {
+                continue;
+            }
+            let (nr, nc) = (nr as usize, nc as usize);
+            let ch = match grid.get(nr).and_then(|row| row.get(nc)) {
+                Some(v) => *v,
+                None => continue,
+            };
+            if let Some(i) = owners[nr][nc] {
+                touched.insert(i);
+            } else if ch != ' ' && !visited[nr][nc] {
+                visited[nr][nc] = true;
+                stack.push((nr, nc));
+            }
+        }
+    }
+    (cells, touched)
+}
+
+// 每一步一个 svg，用于幻灯片逐页放映
+// svgbob 按可见内容确定大小，这里统一为完整的图的大小，切换时图的位置不变
+pub fn to_step_svgs(map: &AMap, options: &ASvgOptions) -> Result<Vec<String>, String> {
+    let svgs: Vec<String> = step_frames(map)?
+        .iter()
+        .map(|ascii| ascii_to_svg(ascii.as_str(), options))
+        .collect();
+    let (width, height) = svgs.last().map(|svg| size(svg)).unwrap_or_default();
+    Ok(svgs
+        .iter()
+        .map(|svg| {
+            let (w, h) = size(svg);
+            let from = format!("width=\"{}\" height=\"{}\"", w, h);
+            let to = format!("width=\"{}\" height=\"{}\"", width, height);
+            // <svg> 和背景 <rect> 的大小
+            svg.replacen(from.as_str(), to.as_str(), 2)
+        })
+        .collect())
+}
+
+// <svg> 的宽和高
+fn size(svg: &str) -> (f32, f32) {
+    let tag = &svg[..svg.find('>').unwrap_or(svg.len())];
+    let attr = |name: &str| -> f32 {
+        tag.find(format!(" {}=\"", name).as_str())
+            .and_then(|at| tag[at + name.len() + 3..].split('"').next())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0)
+    };
+    (attr("width"), attr("height"))
+}
+
+// 各帧的 <defs> 相同，只保留第一帧的，避免 marker 的 id 重复
+fn without_defs(svg: &str) -> String {
+    match (svg.find("<defs>"), svg.find("</defs>")) {
+        (Some(start), Some(end)) if start < end => {
+            let end = end + "</defs>".len();
+            // 连同所在的行一起去掉
+            let head = svg[..start].trim_end_matches(' ');
+            format!("{}{}", head, svg[end..].trim_start_matches('\n'))
+        }
+        _ => svg.to_string(),
+    }
+}
+
+// 所有步骤叠放在一个 svg 中，通过 css 动画每隔 interval 秒淡入下一步
+// 后面的帧包含前面帧的全部内容，并且有不透明的背景，淡入后完全覆盖前一帧
+pub fn to_animated_svg(map: &AMap, options: &ASvgOptions, interval: f32) -> Result<String, String> {
+    let frames = to_step_svgs(map, options)?;
+    let (width, height) = frames.last().map(|svg| size(svg)).unwrap_or_default();
+    let mut content = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">
+  <style>
+@keyframes asciibox-step {{ from {{ opacity: 0; }} to {{ opacity: 1; }} }}
+.asciibox-step {{ opacity: 0; animation: asciibox-step {FADE_IN}s ease-in forwards; }}
+  </style>
+"
+    );
+    for (k, svg) in frames.iter().enumerate() {
+        match k {
+            0 => content.push_str("  <g>\n"),
+            _ => content.push_str(
+                format!(
+                    "  <g class=\"asciibox-step\" style=\"animation-delay: {}s\">\n",
+                    k as f32 * interval
+                )
+                .as_str(),
+            ),
+        }
+        match k {
+            0 => content.push_str(svg.trim_end()),
+            _ => content.push_str(without_defs(svg).trim_end()),
+        }
+        content.push_str("\n  </g>\n");
+    }
+    content.push_str("</svg>\n");
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::diagram::{parse_map, DiagramOptions};
+
+    #[test]
+    fn test_step_frames() {
+        let options = DiagramOptions::default();
+        let map = parse_map("graph LR\na --> b\na --> c\nc --> d", &options).unwrap();
+        let frames = step_frames(&map).unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].trim_end(), map.render().trim_end());
+        assert!(frames[0].contains("| a |---->| b |") && !frames[0].contains("c |"));
+        assert!(frames[1].contains("'->| c |") && !frames[1].contains("| d |"));
+        // 行的宽度不变，布局与完整的图相同
+        assert_eq!(
+            frames[0].lines().map(|l| l.len()).collect::<Vec<_>>(),
+            frames[2].lines().map(|l| l.len()).collect::<Vec<_>>()
+        );
+
+        let source = "graph LR\n%% step 1\na --> b\n%% step 2\na --> c\nc --> d";
+        let map = parse_map(source, &options).unwrap();
+        assert_eq!(step_frames(&map).unwrap().len(), 2);
+        let svg = to_animated_svg(&map, &ASvgOptions::default(), 1.5).unwrap();
+        assert_eq!(svg.matches("<svg ").count(), 3);
+        assert!(svg.contains("style=\"animation-delay: 1.5s\""));
+        assert!(svg.ends_with("</g>\n</svg>\n"));
+        // marker 只定义一次
+        assert_eq!(svg.matches("<defs>").count(), 1);
+        assert_eq!(svg.matches("<marker id=\"arrow\"").count(), 1);
+        let svgs = to_step_svgs(&map, &ASvgOptions::default()).unwrap();
+        assert_eq!(size(&svgs[0]), size(&svgs[1]));
+
+        let map = parse_map("graph LR", &options).unwrap();
+        assert!(to_animated_svg(&map, &ASvgOptions::default(), 1.0).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use serde::Deserialize;
+use svgbob::{to_svg_with_settings, Settings};
+
+use super::links::apply_links;
+use super::tags::apply_dangling_tags;
+
+// svg 的配色，Auto 时浅色为默认，通过 prefers-color-scheme 在深色页面中切换为深色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ASvgTheme {
+    #[default]
+    Light,
+    Dark,
+    Auto,
+}
+
+impl ASvgTheme {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "light" => Some(ASvgTheme::Light),
+            "dark" => Some(ASvgTheme::Dark),
+            "auto" => Some(ASvgTheme::Auto),
+            _ => None,
+        }
+    }
+}
+
+// svgbob 生成 svg 时的样式，默认值与 svgbob 相同
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASvgOptions {
+    pub font_family: String,
+    pub font_size: usize,
+    pub stroke_width: f32,
+    // 线条和文字颜色
+    pub stroke_color: String,
+    // 实心图形，例如箭头的颜色
+    pub fill_color: String,
+    // 背景及 box 内部的颜色
+    pub background: String,
+    // 每个字符单元对应的像素
+    pub scale: f32,
+    // 不绘制背景，box 内部同样透明
+    pub transparent: bool,
+    pub theme: ASvgTheme,
+    // 深色配色，与 Adwaita 深色模式一致
+    pub dark_stroke_color: String,
+    pub dark_fill_color: String,
+    pub dark_background: String,
+}
+
+impl Default for ASvgOptions {
+    fn default() -> Self {
+        let settings = Settings::default();
+        Self {
+            font_family: settings.font_family,
+            font_size: settings.font_size,
+            stroke_width: settings.stroke_width,
+            stroke_color: settings.stroke_color,
+            fill_color: settings.fill_color,
+            background: settings.background,
+            scale: settings.scale,
+            transparent: false,
+            theme: ASvgTheme::Light,
+            dark_stroke_color: "#deddda".to_string(),
+            dark_fill_color: "#deddda".to_string(),
+            dark_background: "#1e1e1e".to_string(),
+        }
+    }
+}
+
+fn positive(key: &str, value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(v) if v > 0.0 && v.is_finite() => Ok(v),
+        _ => Err(format!("{} requires a positive number", key)),
+    }
+}
+
+impl ASvgOptions {
+    // 按名称设置单个选项，名称与 asciibox.toml 中 [svg] 的字段相同
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "font_family" => self.font_family = value.to_string(),
+            "font_size" => self.font_size = positive(key, value)?.round() as usize,
+            "stroke_width" => self.stroke_width = positive(key, value)?,
+            "stroke_color" => self.stroke_color = value.to_string(),
+            "fill_color" => self.fill_color = value.to_string(),
+            "background" => self.background = value.to_string(),
+            "scale" => self.scale = positive(key, value)?,
+            "transparent" => match value {
+                "true" => self.transparent = true,
+                "false" => self.transparent = false,
+                _ => return Err(format!("{} requires true or false", key)),
+            },
+            "theme" => match ASvgTheme::from_name(value) {
+                Some(v) => self.theme = v,
+                None => return Err(format!("{} requires light, dark or auto", key)),
+            },
+            "dark_stroke_color" => self.dark_stroke_color = value.to_string(),
+            "dark_fill_color" => self.dark_fill_color = value.to_string(),
+            "dark_background" => self.dark_background = value.to_string(),
+            _ => return Err(format!("unknown svg option '{}'", key)),
+        }
+        Ok(())
+    }
+
+    // 当前配色下的线条、实心图形和背景颜色
+    fn colors(&self, dark: bool) -> (String, String, String) {
+        let (stroke, fill, background) = match dark {
+            true => (
+                &self.dark_stroke_color,
+                &self.dark_fill_color,
+                &self.dark_background,
+            ),
+            false => (&self.stroke_color, &self.fill_color, &self.background),
+        };
+        let background = match self.transparent {
+            true => "transparent".to_string(),
+            false => background.clone(),
+        };
+        (stroke.clone(), fill.clone(), background)
+    }
+
+    pub fn settings(&self) -> Settings {
+        let (stroke_color, fill_color, background) = self.colors(self.theme == ASvgTheme::Dark);
+        Settings {
+            font_size: self.font_size,
+            font_family: self.font_family.clone(),
+            fill_color,
+            background,
+            stroke_color,
+            stroke_width: self.stroke_width,
+            scale: self.scale,
+            include_backdrop: !self.transparent,
+            ..Settings::default()
+        }
+    }
+
+    // 深色页面中覆盖 svgbob 样式的颜色，选择器与 svgbob 生成的样式相同
+    fn dark_style(&self) -> String {
+        let (stroke, fill, background) = self.colors(true);
+        format!(
+            "@media (prefers-color-scheme: dark) {{
+.svgbob line, .svgbob path, .svgbob circle, .svgbob rect, .svgbob polygon {{ stroke: {stroke}; }}
+.svgbob text {{ fill: {stroke}; }}
+.svgbob rect.backdrop, .svgbob .bg_filled, .svgbob .nofill {{ fill: {background}; }}
+.svgbob .filled {{ fill: {fill}; }}
+}}
+"
+        )
+    }
+}
+
+// 将 svgbob ascii 图转换为 svg
+pub fn ascii_to_svg(ascii: &str, options: &ASvgOptions) -> String {
+    let svg = to_svg_with_settings(ascii, &options.settings());
+    let svg = apply_dangling_tags(ascii, svg.as_str(), options.scale);
+    let svg = apply_links(ascii, svg.as_str());
+    if options.theme != ASvgTheme::Auto {
+        return svg;
+    }
+    // 放在图例之前，节点自身的颜色优先
+    match legend_start(ascii, svg.as_str()).or(svg.find("</style>")) {
+        Some(i) => format!("{}{}{}", &svg[..i], options.dark_style(), &svg[i..]),
+        None => svg,
+    }
+}
+
+// svgbob 将图例中的样式追加在 <style> 的末尾，返回第一条的位置
+fn legend_start(ascii: &str, svg: &str) -> Option<usize> {
+    let legend = &ascii[ascii.find("# Legend:")?..];
+    let (class, _) = legend.lines().nth(1)?.split_once('=')?;
+    svg.find(format!(".svgbob .{}{{", class.trim()).as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_to_svg() {
+        let ascii = ".---.\n| a |\n'---'\n";
+        assert_eq!(
+            ascii_to_svg(ascii, &ASvgOptions::default()),
+            svgbob::to_svg(ascii)
+        );
+        let mut options = ASvgOptions::default();
+        options.set("font_family", "Noto Sans Mono").unwrap();
+        options.set("stroke_width", "1.5").unwrap();
+        options.set("transparent", "true").unwrap();
+        let svg = ascii_to_svg(ascii, &options);
+        assert!(svg.contains("font-family: Noto Sans Mono;"));
+        assert!(svg.contains("stroke-width: 1.5;"));
+        assert!(!svg.contains("class=\"backdrop\""));
+        assert!(options.set("font_size", "0").is_err());
+        assert!(options.set("colour", "red").is_err());
+
+        let mut options = ASvgOptions::default();
+        options.set("theme", "dark").unwrap();
+        let svg = ascii_to_svg(ascii, &options);
+        assert!(svg.contains("stroke: #deddda;") && !svg.contains("prefers-color-scheme"));
+        options.set("theme", "auto").unwrap();
+        let svg = ascii_to_svg(ascii, &options);
+        assert!(svg.contains("stroke: black;"));
+        assert!(svg.contains("@media (prefers-color-scheme: dark)"));
+        // 深色样式在 </style> 之前，png 渲染时忽略
+        assert!(svg.find("@media").unwrap() < svg.find("</style>").unwrap());
+        assert!(options.set("theme", "blue").is_err());
+    }
+}
@@ -0,0 +1,222 @@
+use crate::core::utils::cn_length;
+
+// svgbob 只给独立的 box 加上 {c0} 标签对应的 css 类，连着箭头的 box 被拆成线段，
+// 标签按普通文字输出。这里去掉这些文字，在 box 下方补充填充的矩形，并给边框线段加上样式类
+pub(super) fn apply_dangling_tags(ascii: &str, svg: &str, scale: f32) -> String {
+    let end = match ascii.find("# Legend:") {
+        Some(v) => v,
+        None => return svg.to_string(),
+    };
+    let grid = to_grid(&ascii[..end]);
+    let mut svg = svg.to_string();
+    let mut rects: Vec<String> = Vec::new();
+    while let Some((begin, stop, classes, x, y)) = find_tag_text(svg.as_str()) {
+        svg.replace_range(begin..stop, "");
+        // svgbob 中文字的位置为 (col * scale + scale / 4, row * 2 * scale + 1.5 * scale)
+        let col = ((x - scale / 4.0) / scale).round() as usize;
+        let row = ((y - scale * 1.5) / (scale * 2.0)).round() as usize;
+        let (left, top, right, bottom, round) = match box_bounds(&grid, row, col) {
+            Some(v) => v,
+            None => continue,
+        };
+        // 线段的坐标位于字符单元的中心
+        let x1 = (left as f32 + 0.5) * scale;
+        let x2 = (right as f32 + 0.5) * scale;
+        let y1 = (top as f32 + 0.5) * scale * 2.0;
+        let y2 = (bottom as f32 + 0.5) * scale * 2.0;
+        svg = add_classes(svg.as_str(), (x1, y1, x2, y2), classes.as_str());
+        let rx = if round { scale / 2.0 } else { 0.0 };
+        rects.push(format!(
+            "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"nofill {}\" rx=\"{}\"></rect>\n",
+            x1,
+            y1,
+            x2 - x1,
+            y2 - y1,
+            classes,
+            rx
+        ));
+    }
+    if rects.is_empty() {
+        return svg;
+    }
+    // 填充的矩形放在背景之后，位于边框线段的下方
+    let at = match svg.find("<rect class=\"backdrop\"") {
+        Some(v) => v + svg[v..].find('\n').map_or(0, |n| n + 1),
+        None => svg.find("</defs>").map_or(0, |v| v + "</defs>\n".len()),
+    };
+    svg.insert_str(at.min(svg.len()), rects.concat().as_str());
+    svg
+}
+
+// 按显示宽度展开字符，中文占用两个单元
+pub(super) fn to_grid(ascii: &str) -> Vec<Vec<char>> {
+    ascii
+        .lines()
+        .map(|line| {
+            let mut row = Vec::new();
+            for c in line.chars() {
+                row.push(c);
+                if cn_length(c.to_string().as_str()) == 2 {
+                    row.push('\0');
+                }
+            }
+            row
+        })
+        .collect()
+}
+
+// 第一个内容为 {c0,c1} 的 <text>，返回其位置、样式类和坐标
+fn find_tag_text(svg: &str) -> Option<(usize, usize, String, f32, f32)> {
+    let mut from = 0;
+    while let Some(i) = svg[from..].find("<text ") {
+        let begin = from + i;
+        let close = begin + svg[begin..].find("</text>")?;
+        let stop = close + "</text>".len();
+        let content_at = begin + svg[begin..close].find('>')? + 1;
+        let content = &svg[content_at..close];
+        from = stop;
+        let classes = match content.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+            Some(v) if is_classes(v) => v.replace(',', " "),
+            _ => continue,
+        };
+        let attr = |name: &str| -> Option<f32> {
+            let tag = &svg[begin..content_at];
+            let at = tag.find(format!(" {}=\"", name).as_str())? + name.len() + 3;
+            tag[at..].split('"').next()?.parse().ok()
+        };
+        let end = match svg[stop..].starts_with('\n') {
+            true => stop + 1,
+            false => stop,
+        };
+        // 同时去掉前面的缩进
+        let begin = svg[..begin].trim_end_matches(' ').len();
+        return Some((begin, end, classes, attr("x")?, attr("y")?));
+    }
+    None
+}
+
+fn is_classes(text: &str) -> bool {
+    text.split(',').all(|v| {
+        v.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && v.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+// 标签左侧为 box 的左边框，返回 box 的 (左, 上, 右, 下) 边框位置及是否为圆角
+fn box_bounds(
+    grid: &[Vec<char>],
+    row: usize,
+    col: usize,
+) -> Option<(usize, usize, usize, usize, bool)> {
+    let at = |r: usize, c: usize| grid.get(r).and_then(|v| v.get(c)).copied();
+    let left = col.checked_sub(1)?;
+    if at(row, left)? != '|' {
+        return None;
+    }
+    let mut top = row;
+    while at(top, left)? == '|' {
+        top = top.checked_sub(1)?;
+    }
+    let mut bottom = row;
+    while at(bottom, left)? == '|' {
+        bottom += 1;
+    }
+    let corner = at(top, left)?;
+    let mut right = left + 1;
+    while at(top, right)? == '-' {
+        right += 1;
+    }
+    Some((left, top, right, bottom, corner == '.'))
+}
+
+// 给完全位于 box 边框上的线段和圆角加上样式类
+fn add_classes(svg: &str, bounds: (f32, f32, f32, f32), classes: &str) -> String {
+    let (x1, y1, x2, y2) = bounds;
+    let inside = |x: f32, y: f32| x >= x1 - 0.1 && x <= x2 + 0.1 && y >= y1 - 0.1 && y <= y2 + 0.1;
+    let lines: Vec<String> = svg
+        .split('\n')
+        .map(|line| {
+            let points = element_points(line.trim_start());
+            if points.len() < 2 || !points.iter().all(|(x, y)| inside(*x, *y)) {
+                return line.to_string();
+            }
+            match line.find(" class=\"") {
+                Some(at) => {
+                    let at = at + " class=\"".len();
+                    let end = at + line[at..].find('"').unwrap_or(0);
+                    format!("{} {}{}", &line[..end], classes, &line[end..])
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+// <line> 的两个端点，或者 <path> 的起点和终点
+pub(super) fn element_points(element: &str) -> Vec<(f32, f32)> {
+    let attr = |name: &str| -> Option<&str> {
+        let at = element.find(format!(" {}=\"", name).as_str())? + name.len() + 3;
+        element[at..].split('"').next()
+    };
+    if element.starts_with("<line ") {
+        let values: Vec<Option<f32>> = ["x1", "y1", "x2", "y2"]
+            .iter()
+            .map(|v| attr(v).and_then(|v| v.parse().ok()))
+            .collect();
+        return match values[..] {
+            [Some(a), Some(b), Some(c), Some(d)] => vec![(a, b), (c, d)],
+            _ => Vec::new(),
+        };
+    }
+    if element.starts_with("<path ") {
+        // 例如 M 8,8 A 4,4 0,0,0 4,12，取第一个和最后一个坐标
+        let points: Vec<(f32, f32)> = attr("d")
+            .unwrap_or("")
+            .split_whitespace()
+            .filter_map(|v| {
+                let (x, y) = v.split_once(',')?;
+                Some((x.parse().ok()?, y.parse().ok()?))
+            })
+            .collect();
+        return match (points.first(), points.last()) {
+            (Some(first), Some(last)) if points.len() > 1 => vec![*first, *last],
+            _ => Vec::new(),
+        };
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::diagram::{render, DiagramOptions};
+    use crate::core::export::{ascii_to_svg, ASvgOptions};
+
+    #[test]
+    fn test_apply_dangling_tags() {
+        let options = DiagramOptions {
+            css_tags: true,
+            ..DiagramOptions::default()
+        };
+        let source = "graph LR\nclassDef team fill:#f9f,stroke:#333\na[Start]:::team --> b(Next)\nstyle b stroke:red\nlinkStyle 0 stroke:blue";
+        let ascii = render(source, &options);
+        assert!(ascii.contains("|{c0}  Start"));
+        assert!(
+            ascii.ends_with("# Legend:\nc0 = {fill: #f9f; stroke: #333;}\nc1 = {stroke: red;}\n")
+        );
+        let svg = ascii_to_svg(ascii.as_str(), &ASvgOptions::default());
+        // 标签不会作为文字出现
+        assert!(!svg.contains(">{c"));
+        assert!(svg.contains(".svgbob .c0{ fill: #f9f; stroke: #333; }"));
+        assert!(svg.contains("class=\"nofill c0\""));
+        assert!(svg.contains("class=\"solid c0\""));
+        assert!(svg.contains("c1\""));
+        // 箭头不属于 box
+        assert!(svg.contains("class=\"solid\"></line>"));
+        // 纯文本输出中没有标签和图例
+        assert_eq!(
+            render(source, &DiagramOptions::default()),
+            "+-------+   .------.\n| Start |-->| Next |\n+-------+   '------'\n"
+        );
+    }
+}
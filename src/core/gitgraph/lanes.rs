@@ -0,0 +1,339 @@
+use super::parse::{parse_command, AGitCommand};
+use crate::core::utils::ACanvas;
+use std::collections::HashMap;
+
+// 相邻两条分支之间的列宽，中间一列留给斜线
+const LANE_W: usize = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ACommitKind {
+    Normal,
+    Reverse,
+    Highlight,
+    Merge,
+    CherryPick,
+}
+
+#[derive(Debug, Clone)]
+pub struct ACommit {
+    pub id: String,
+    pub branch: usize,
+    pub parents: Vec<usize>,
+    pub tag: String,
+    pub kind: ACommitKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct ABranch {
+    pub name: String,
+    pub order: usize,
+    // 分支当前指向的提交
+    pub head: Option<usize>,
+}
+
+// mermaid gitGraph，按时间从上到下绘制，每条分支占用一列
+#[derive(Debug, Clone)]
+pub struct AGitGraph {
+    pub commits: Vec<ACommit>,
+    pub branches: Vec<ABranch>,
+    current: usize,
+}
+
+impl AGitGraph {
+    pub fn new() -> Self {
+        Self {
+            commits: Vec::new(),
+            branches: vec![ABranch {
+                name: "main".to_string(),
+                order: 0,
+                head: None,
+            }],
+            current: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn find_branch(&self, name: &str) -> Option<usize> {
+        self.branches.iter().position(|b| b.name == name)
+    }
+
+    fn find_commit(&self, id: &str) -> Option<usize> {
+        self.commits.iter().position(|c| c.id == id)
+    }
+
+    fn add_commit(
+        &mut self,
+        options: &HashMap<String, String>,
+        kind: ACommitKind,
+        parents: Vec<usize>,
+    ) {
+        let kind = match options.get("type").map(|s| s.as_str()) {
+            Some("REVERSE") => ACommitKind::Reverse,
+            Some("HIGHLIGHT") => ACommitKind::Highlight,
+            _ => kind,
+        };
+        let idx = self.commits.len();
+        let id = match options.get("id") {
+            Some(v) => v.clone(),
+            None => format!("{}-{}", idx, self.branches[self.current].name),
+        };
+        self.commits.push(ACommit {
+            id,
+            branch: self.current,
+            parents,
+            tag: options.get("tag").cloned().unwrap_or_default(),
+            kind,
+        });
+        self.branches[self.current].head = Some(idx);
+    }
+
+    fn do_command(&mut self, command: AGitCommand) {
+        let head = self.branches[self.current].head;
+        match command {
+            AGitCommand::Commit(options) => {
+                self.add_commit(&options, ACommitKind::Normal, head.into_iter().collect());
+            }
+            AGitCommand::Branch(name, options) => {
+                if self.find_branch(name.as_str()).is_some() {
+                    return;
+                }
+                let order = match options.get("order").and_then(|v| v.parse().ok()) {
+                    Some(v) => v,
+                    None => self.branches.len(),
+                };
+                self.branches.push(ABranch { name, order, head });
+                self.current = self.branches.len() - 1;
+            }
+            AGitCommand::Checkout(name) => {
+                if let Some(v) = self.find_branch(name.as_str()) {
+                    self.current = v;
+                }
+            }
+            AGitCommand::Merge(name, options) => {
+                let other = self
+                    .find_branch(name.as_str())
+                    .and_then(|b| self.branches[b].head);
+                if let (Some(h), Some(o)) = (head, other) {
+                    if h != o {
+                        self.add_commit(&options, ACommitKind::Merge, vec![h, o]);
+                    }
+                }
+            }
+            AGitCommand::CherryPick(mut options) => {
+                let picked = match options.get("id") {
+                    Some(v) => v.clone(),
+                    None => return,
+                };
+                if self.find_commit(picked.as_str()).is_none() {
+                    return;
+                }
+                // 被挑选的 id 只作为标签显示，新提交使用自动 id
+                options.remove("id");
+                let tag = match options.get("tag") {
+                    Some(v) => format!("cherry-pick: {}, {}", picked, v),
+                    None => format!("cherry-pick: {}", picked),
+                };
+                options.insert("tag".to_string(), tag);
+                self.add_commit(
+                    &options,
+                    ACommitKind::CherryPick,
+                    head.into_iter().collect(),
+                );
+            }
+        }
+    }
+
+    pub fn build_commits(&mut self, content: &str) {
+        self.clear();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("%%") || line.starts_with("gitGraph") {
+                continue;
+            }
+            if let Some(command) = parse_command(line) {
+                self.do_command(command);
+            }
+        }
+    }
+
+    // 依据 order 给分支分配列，只给有提交的分支分配
+    fn lanes(&self) -> Vec<usize> {
+        let mut ids: Vec<usize> = (0..self.branches.len())
+            .filter(|&b| self.commits.iter().any(|c| c.branch == b))
+            .collect();
+        ids.sort_by_key(|&b| (self.branches[b].order, b));
+        let mut lanes = vec![0; self.branches.len()];
+        for (lane, b) in ids.iter().enumerate() {
+            lanes[*b] = lane;
+        }
+        lanes
+    }
+
+    fn commit_char(commit: &ACommit) -> char {
+        match commit.kind {
+            ACommitKind::Highlight => '*',
+            ACommitKind::Reverse => 'x',
+            ACommitKind::Merge => 'O',
+            _ => 'o',
+        }
+    }
+
+    // 横线穿过其它分支的竖线时用 + 连接
+    fn cross_hline(canvas: &mut ACanvas, x1: usize, x2: usize, y: usize) {
+        for x in x1.min(x2)..=x1.max(x2) {
+            let c = if canvas.get(x, y) == '|' { '+' } else { '-' };
+            canvas.put(x, y, c);
+        }
+    }
+
+    // 相邻分支之间的斜线，两条斜线交叉时用 X
+    fn diagonal(canvas: &mut ACanvas, x: usize, y: usize, c: char) {
+        let c = match canvas.get(x, y) {
+            ' ' => c,
+            v if v == c => c,
+            _ => 'X',
+        };
+        canvas.put(x, y, c);
+    }
+
+    pub fn render(&self) -> String {
+        if self.commits.is_empty() {
+            return String::new();
+        }
+        let lanes = self.lanes();
+        let lane_x = |c: &ACommit| lanes[c.branch] * LANE_W;
+        let row = |i: usize| i * 2;
+        let mut canvas = ACanvas::new(0, 0);
+
+        // 同一分支上的竖线
+        for (i, commit) in self.commits.iter().enumerate() {
+            for p in commit.parents.iter() {
+                let parent = &self.commits[*p];
+                if lanes[parent.branch] == lanes[commit.branch] {
+                    canvas.vline(lane_x(commit), row(*p) + 1, row(i) - 1, '|');
+                }
+            }
+        }
+        // 分支的创建和合并，与 git log --graph 一样在相邻分支之间用 / 和 \ 连接
+        for (i, commit) in self.commits.iter().enumerate() {
+            for (k, p) in commit.parents.iter().enumerate() {
+                let parent = &self.commits[*p];
+                let (px, cx) = (lane_x(parent), lane_x(commit));
+                if px == cx {
+                    continue;
+                }
+                let right = cx > px;
+                if k == 0 {
+                    // 从父提交斜向下进入子提交的分支，不相邻时先在父提交所在行横向引出
+                    let (dx, c) = if right { (cx - 1, '\\') } else { (cx + 1, '/') };
+                    let hx = if right { dx - 1 } else { dx + 1 };
+                    if hx != px {
+                        let from = if right { px + 1 } else { px - 1 };
+                        Self::cross_hline(&mut canvas, from, hx, row(*p));
+                    }
+                    Self::diagonal(&mut canvas, dx, row(*p) + 1, c);
+                    canvas.vline(cx, row(*p) + 2, row(i) - 1, '|');
+                } else {
+                    // 从被合并的提交向下，在合并提交的上一行斜向合入，不相邻时再横向连接
+                    let (dx, c) = if right { (px + 1, '\\') } else { (px - 1, '/') };
+                    canvas.vline(px, row(*p) + 1, row(i) - 2, '|');
+                    Self::diagonal(&mut canvas, dx, row(i) - 1, c);
+                    let hx = if right { dx + 1 } else { dx - 1 };
+                    if hx != cx {
+                        let to = if right { cx - 1 } else { cx + 1 };
+                        Self::cross_hline(&mut canvas, hx, to, row(i));
+                    }
+                }
+            }
+        }
+
+        // 提交点和说明文字
+        let text_x = lanes.iter().max().unwrap_or(&0) * LANE_W + 3;
+        for (i, commit) in self.commits.iter().enumerate() {
+            canvas.put(lane_x(commit), row(i), Self::commit_char(commit));
+            let mut text = commit.id.clone();
+            if !commit.tag.is_empty() {
+                text.push_str(format!(" ({})", commit.tag).as_str());
+            }
+            let branch = &self.branches[commit.branch];
+            if branch.head == Some(i) {
+                text.push_str(format!(" [{}]", branch.name).as_str());
+            }
+            canvas.text(text_x, row(i), text.as_str());
+        }
+        canvas.render()
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.build_commits(content);
+        self.render()
+    }
+}
+
+impl Default for AGitGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_graph_build() {
+        let mut graph = AGitGraph::new();
+        graph.build_commits(
+            "gitGraph
+            commit id: \"A\"
+            branch develop
+            commit
+            checkout main
+            merge develop tag: \"v1\"
+            cherry-pick id: \"A\"",
+        );
+        assert_eq!(graph.commits.len(), 4);
+        assert_eq!(graph.commits[1].id, "1-develop");
+        assert_eq!(graph.commits[1].parents, vec![0]);
+        assert_eq!(graph.commits[2].kind, ACommitKind::Merge);
+        assert_eq!(graph.commits[2].parents, vec![0, 1]);
+        assert_eq!(graph.commits[3].tag, "cherry-pick: A");
+    }
+
+    #[test]
+    fn test_git_graph_render() {
+        let mut graph = AGitGraph::new();
+        let result = "
+o    A
+|\\
+| o  1-develop [develop]
+|/
+O    2-main (v1) [main]
+";
+        let code =
+            "commit id: \"A\"\nbranch develop\ncommit\ncheckout main\nmerge develop tag: \"v1\"";
+        assert_eq!(graph.load_content(code), result[1..]);
+        // 不相邻的分支先横向连接，再用斜线进入目标分支
+        let result = "
+o      A
+|\\
+| o    1-develop
+| |\\
+| | o  2-feature [feature]
+| | |
+o | |  3-main
+| |/
+O-+    4-main [main]
+| |
+| o    5-develop
+ \\|
+  O    6-develop [develop]
+";
+        let code = "commit id: \"A\"\nbranch develop\ncommit\nbranch feature\ncommit
+checkout main\ncommit\nmerge feature\ncheckout develop\ncommit\nmerge main";
+        assert_eq!(graph.load_content(code), result[1..]);
+    }
+}
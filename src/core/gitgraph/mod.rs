@@ -0,0 +1,5 @@
+mod lanes;
+mod parse;
+
+pub use lanes::AGitGraph;
+pub use parse::parse_command;
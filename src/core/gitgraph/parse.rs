@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AGitCommand {
+    Commit(HashMap<String, String>),
+    Branch(String, HashMap<String, String>),
+    Checkout(String),
+    Merge(String, HashMap<String, String>),
+    CherryPick(HashMap<String, String>),
+}
+
+// 解析 key: value 形式的参数，value 可以带引号
+// id: "Alpha" tag: "v1.0" type: HIGHLIGHT
+pub fn parse_options(input: &str) -> HashMap<String, String> {
+    let mut options = HashMap::new();
+    let mut remain = input.trim();
+    while let Some((key, value)) = remain.split_once(':') {
+        let key = key.trim().to_string();
+        let value = value.trim_start();
+        let (v, r) = match value.strip_prefix('"') {
+            Some(v) => v.split_once('"').unwrap_or((v, "")),
+            None => value.split_once(' ').unwrap_or((value, "")),
+        };
+        options.insert(key, v.to_string());
+        remain = r.trim();
+    }
+    options
+}
+
+// 拆分出第一个单词作为参数名，例如 branch develop order: 1
+fn split_name(input: &str) -> (String, &str) {
+    let input = input.trim();
+    let (name, remain) = input.split_once(' ').unwrap_or((input, ""));
+    (name.trim_matches('"').to_string(), remain)
+}
+
+pub fn parse_command(line: &str) -> Option<AGitCommand> {
+    let line = line.trim();
+    let (key, remain) = line.split_once(' ').unwrap_or((line, ""));
+    let command = match key {
+        "commit" => AGitCommand::Commit(parse_options(remain)),
+        "branch" => {
+            let (name, remain) = split_name(remain);
+            AGitCommand::Branch(name, parse_options(remain))
+        }
+        "checkout" | "switch" => AGitCommand::Checkout(split_name(remain).0),
+        "merge" => {
+            let (name, remain) = split_name(remain);
+            AGitCommand::Merge(name, parse_options(remain))
+        }
+        "cherry-pick" => AGitCommand::CherryPick(parse_options(remain)),
+        _ => return None,
+    };
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_command_parse() {
+        let options = parse_options("id: \"Alpha beta\" tag: v1.0 type: HIGHLIGHT");
+        assert_eq!(options.get("id").unwrap(), "Alpha beta");
+        assert_eq!(options.get("tag").unwrap(), "v1.0");
+        assert_eq!(options.get("type").unwrap(), "HIGHLIGHT");
+
+        assert_eq!(
+            parse_command("checkout develop"),
+            Some(AGitCommand::Checkout("develop".to_string()))
+        );
+        match parse_command("branch feature order: 2") {
+            Some(AGitCommand::Branch(name, options)) => {
+                assert_eq!(name, "feature");
+                assert_eq!(options.get("order").unwrap(), "2");
+            }
+            _ => panic!("parse branch failed"),
+        }
+        assert_eq!(parse_command("gitGraph"), None);
+    }
+}
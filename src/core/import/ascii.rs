@@ -0,0 +1,458 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::core::svgbob::ASharp;
+use crate::core::utils::cn_length;
+
+// 宽字符占两列，第二列用该字符占位
+const WIDE: char = '\0';
+
+// 上下左右
+const DIRS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const UP: usize = 0;
+const DOWN: usize = 1;
+const LEFT: usize = 2;
+const RIGHT: usize = 3;
+
+// 从 ascii 图中识别出的 box，坐标包含边框
+#[derive(Debug, Clone, PartialEq)]
+pub struct ABox {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+    pub text: String,
+    pub sharp: ASharp,
+}
+
+// 两个 box 之间的连线，direct 为箭头进入 dst 的方向
+#[derive(Debug, Clone, PartialEq)]
+pub struct ALink {
+    pub src: usize,
+    pub dst: usize,
+    pub direct: usize,
+    // 两端都有箭头
+    pub double: bool,
+    // 两端都没有箭头
+    pub plain: bool,
+}
+
+// 线条字符可以连接的方向
+fn arms(c: char) -> [bool; 4] {
+    match c {
+        '-' => [false, false, true, true],
+        '|' => [true, true, false, false],
+        '+' => [true, true, true, true],
+        '.' => [false, true, true, true],
+        '\'' => [true, false, true, true],
+        '>' | '<' => [false, false, true, true],
+        'v' | '^' => [true, true, false, false],
+        _ => [false; 4],
+    }
+}
+
+// 箭头指向的方向
+fn head(c: char) -> Option<usize> {
+    match c {
+        '^' => Some(UP),
+        'v' => Some(DOWN),
+        '<' => Some(LEFT),
+        '>' => Some(RIGHT),
+        _ => None,
+    }
+}
+
+// 识别 svgbob 风格的 ascii 图，例如 AMap 的输出，还原为 mermaid 源码
+// 只识别由 .---. / +---+ 组成的 box 以及连接它们的 - | + . ' 线条和箭头
+#[derive(Debug, Default)]
+pub struct AAsciiReader {
+    grid: Vec<Vec<char>>,
+    pub boxes: Vec<ABox>,
+    pub links: Vec<ALink>,
+}
+
+impl AAsciiReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn at(&self, y: isize, x: isize) -> char {
+        if y < 0 || x < 0 {
+            return ' ';
+        }
+        self.grid
+            .get(y as usize)
+            .and_then(|l| l.get(x as usize))
+            .copied()
+            .unwrap_or(' ')
+    }
+
+    fn build_grid(&mut self, content: &str) {
+        self.grid = content
+            .lines()
+            .map(|line| {
+                let mut row: Vec<char> = Vec::new();
+                for c in line.chars() {
+                    row.push(c);
+                    if cn_length(c.to_string().as_str()) > 1 {
+                        row.push(WIDE);
+                    }
+                }
+                row
+            })
+            .collect();
+    }
+
+    // 以 (y, x) 为左上角尝试识别一个 box
+    fn try_box(&self, y: usize, x: usize) -> Option<ABox> {
+        let (yi, xi) = (y as isize, x as isize);
+        let corner = self.at(yi, xi);
+        let (top, bottom) = match corner {
+            '.' => ('.', '\''),
+            '+' => ('+', '+'),
+            _ => return None,
+        };
+        if self.at(yi, xi + 1) != '-' || self.at(yi + 1, xi) != '|' {
+            return None;
+        }
+        let mut right = xi + 1;
+        while self.at(yi, right) == '-' {
+            right += 1;
+        }
+        if self.at(yi, right) != top || self.at(yi + 1, right) != '|' {
+            return None;
+        }
+        let mut b = yi + 1;
+        while self.at(b, xi) == '|' && self.at(b, right) == '|' {
+            b += 1;
+        }
+        if self.at(b, xi) != bottom || self.at(b, right) != bottom {
+            return None;
+        }
+        if (xi + 1..right).any(|k| self.at(b, k) != '-') {
+            return None;
+        }
+        let text: Vec<String> = (yi + 1..b)
+            .map(|row| {
+                (xi + 1..right)
+                    .map(|k| self.at(row, k))
+                    .filter(|&c| c != WIDE)
+                    .collect::<String>()
+                    .trim()
+                    .to_string()
+            })
+            .filter(|l| !l.is_empty())
+            .collect();
+        Some(ABox {
+            top: y,
+            left: x,
+            bottom: b as usize,
+            right: right as usize,
+            text: text.join("\n"),
+            sharp: match corner {
+                '+' => ASharp::Square,
+                _ => ASharp::Round,
+            },
+        })
+    }
+
+    fn find_boxes(&mut self) {
+        self.boxes.clear();
+        let mut covered: HashSet<(usize, usize)> = HashSet::new();
+        for y in 0..self.grid.len() {
+            for x in 0..self.grid[y].len() {
+                if covered.contains(&(y, x)) {
+                    continue;
+                }
+                if let Some(v) = self.try_box(y, x) {
+                    for row in v.top..=v.bottom {
+                        for col in v.left..=v.right {
+                            covered.insert((row, col));
+                        }
+                    }
+                    self.boxes.push(v);
+                }
+            }
+        }
+    }
+
+    // 边框所在位置对应的 box
+    fn border_map(&self) -> HashMap<(isize, isize), usize> {
+        let mut map = HashMap::new();
+        for (i, b) in self.boxes.iter().enumerate() {
+            let (t, l, bt, r) = (
+                b.top as isize,
+                b.left as isize,
+                b.bottom as isize,
+                b.right as isize,
+            );
+            for x in l..=r {
+                map.insert((t, x), i);
+                map.insert((bt, x), i);
+            }
+            for y in t..=bt {
+                map.insert((y, l), i);
+                map.insert((y, r), i);
+            }
+        }
+        map
+    }
+
+    // 从 box 边框外的线条开始，沿线条找到所有相连的 box
+    fn find_links(&mut self) {
+        self.links.clear();
+        let borders = self.border_map();
+        let mut visited: HashSet<(isize, isize)> = HashSet::new();
+        let mut seeds: Vec<(isize, isize, usize)> = Vec::new();
+        for b in self.boxes.iter() {
+            let (t, l, bt, r) = (
+                b.top as isize,
+                b.left as isize,
+                b.bottom as isize,
+                b.right as isize,
+            );
+            for y in t..=bt {
+                seeds.push((y, l - 1, LEFT));
+                seeds.push((y, r + 1, RIGHT));
+            }
+            for x in l..=r {
+                seeds.push((t - 1, x, UP));
+                seeds.push((bt + 1, x, DOWN));
+            }
+        }
+        for (y, x, dir) in seeds {
+            // 线条需要朝向 box 的一侧可以连接
+            let back = dir ^ 1;
+            if visited.contains(&(y, x)) || borders.contains_key(&(y, x)) {
+                continue;
+            }
+            if !arms(self.at(y, x))[back] {
+                continue;
+            }
+            // 记录连线经过的 box，以及是否由箭头进入
+            let mut touched: Vec<(usize, Option<usize>)> = Vec::new();
+            let mut queue: VecDeque<(isize, isize)> = VecDeque::from([(y, x)]);
+            visited.insert((y, x));
+            while let Some((cy, cx)) = queue.pop_front() {
+                let c = self.at(cy, cx);
+                for (d, (dy, dx)) in DIRS.iter().enumerate() {
+                    if !arms(c)[d] {
+                        continue;
+                    }
+                    let (ny, nx) = (cy + dy, cx + dx);
+                    if let Some(&i) = borders.get(&(ny, nx)) {
+                        let entry = match head(c) {
+                            Some(h) if h == d => Some(d),
+                            _ => None,
+                        };
+                        touched.push((i, entry));
+                        continue;
+                    }
+                    if visited.contains(&(ny, nx)) || !arms(self.at(ny, nx))[d ^ 1] {
+                        continue;
+                    }
+                    visited.insert((ny, nx));
+                    queue.push_back((ny, nx));
+                }
+            }
+            self.add_links(touched);
+        }
+    }
+
+    fn add_links(&mut self, touched: Vec<(usize, Option<usize>)>) {
+        let mut srcs: Vec<usize> = Vec::new();
+        let mut dsts: Vec<(usize, usize)> = Vec::new();
+        for (i, entry) in touched {
+            match entry {
+                Some(d) if !dsts.iter().any(|v| v.0 == i) => dsts.push((i, d)),
+                None if !srcs.contains(&i) => srcs.push(i),
+                _ => {}
+            }
+        }
+        srcs.retain(|i| !dsts.iter().any(|v| v.0 == *i));
+        // 两端都是箭头
+        if srcs.is_empty() && dsts.len() == 2 {
+            self.links.push(ALink {
+                src: dsts[0].0,
+                dst: dsts[1].0,
+                direct: dsts[1].1,
+                double: true,
+                plain: false,
+            });
+            return;
+        }
+        // 没有箭头的连线，从最左上的 box 连接到其他 box
+        if dsts.is_empty() && srcs.len() >= 2 {
+            srcs.sort_by_key(|&i| (self.boxes[i].left, self.boxes[i].top));
+            for &dst in srcs[1..].iter() {
+                self.links.push(ALink {
+                    src: srcs[0],
+                    dst,
+                    direct: RIGHT,
+                    double: false,
+                    plain: true,
+                });
+            }
+            return;
+        }
+        for &src in srcs.iter() {
+            for &(dst, direct) in dsts.iter() {
+                self.links.push(ALink {
+                    src,
+                    dst,
+                    direct,
+                    double: false,
+                    plain: false,
+                });
+            }
+        }
+    }
+
+    // 节点 id，文字可以直接作为 id 时使用文字
+    fn node_ids(&self) -> Vec<String> {
+        let mut used: HashSet<String> = HashSet::new();
+        let mut ids: Vec<String> = Vec::new();
+        for (i, b) in self.boxes.iter().enumerate() {
+            let simple = !b.text.is_empty()
+                && b.text
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+            let id = match simple && !used.contains(&b.text) {
+                true => b.text.clone(),
+                false => format!("n{}", i + 1),
+            };
+            used.insert(id.clone());
+            ids.push(id);
+        }
+        ids
+    }
+
+    fn node_source(&self, i: usize, id: &str) -> String {
+        let b = &self.boxes[i];
+        let text = b.text.replace('\n', "\\n");
+        match b.sharp {
+            ASharp::Square => format!("{}[{}]", id, text),
+            _ if id == b.text => id.to_string(),
+            _ => format!("{}({})", id, text),
+        }
+    }
+
+    // 按 AMap 可以还原布局的顺序输出连线，从没有入边的节点开始广度优先
+    fn ordered_links(&self) -> Vec<&ALink> {
+        let mut order: Vec<usize> = (0..self.boxes.len()).collect();
+        let incoming: HashSet<usize> = self.links.iter().map(|l| l.dst).collect();
+        order.sort_by_key(|&i| (incoming.contains(&i), self.boxes[i].top, self.boxes[i].left));
+        let mut visited: HashSet<usize> = HashSet::new();
+        let mut result: Vec<&ALink> = Vec::new();
+        for start in order {
+            if !visited.insert(start) {
+                continue;
+            }
+            let mut queue: VecDeque<usize> = VecDeque::from([start]);
+            while let Some(i) = queue.pop_front() {
+                let mut outgoing: Vec<&ALink> = self.links.iter().filter(|l| l.src == i).collect();
+                // 水平连线在前，AMap 以首条连线确定起点所在行
+                outgoing.sort_by_key(|l| {
+                    let b = &self.boxes[l.dst];
+                    (l.direct == UP || l.direct == DOWN, b.top, b.left)
+                });
+                for link in outgoing {
+                    result.push(link);
+                    if visited.insert(link.dst) {
+                        queue.push_back(link.dst);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    // 识别 ascii 图并生成 mermaid 源码，每行一条连线，孤立的 box 单独一行
+    pub fn do_read(&mut self, content: &str) -> String {
+        self.build_grid(content);
+        self.find_boxes();
+        self.find_links();
+
+        let ids = self.node_ids();
+        let mut declared: HashSet<usize> = HashSet::new();
+        let mut node = |i: usize| -> String {
+            match declared.insert(i) {
+                true => self.node_source(i, ids[i].as_str()),
+                false => ids[i].clone(),
+            }
+        };
+        let mut lines: Vec<String> = Vec::new();
+        for link in self.ordered_links() {
+            let line = match (link.double, link.direct) {
+                _ if link.plain => format!("{} --- {}", node(link.src), node(link.dst)),
+                (true, _) => format!("{} <--> {}", node(link.src), node(link.dst)),
+                (false, UP) => format!("{} --^ {}", node(link.src), node(link.dst)),
+                (false, DOWN) => format!("{} --v {}", node(link.src), node(link.dst)),
+                // 箭头向左时目标在左侧
+                (false, LEFT) => {
+                    let dst = node(link.dst);
+                    format!("{} <-- {}", dst, node(link.src))
+                }
+                _ => format!("{} --> {}", node(link.src), node(link.dst)),
+            };
+            lines.push(line);
+        }
+        let orphans: Vec<usize> = (0..self.boxes.len())
+            .filter(|i| !self.links.iter().any(|l| l.src == *i || l.dst == *i))
+            .collect();
+        for i in orphans {
+            lines.push(node(i));
+        }
+        let mut result = lines.join("\n");
+        result.push('\n');
+        result
+    }
+}
+
+// 将 ascii 图还原为 mermaid 源码
+pub fn ascii_to_mermaid(content: &str) -> String {
+    AAsciiReader::new().do_read(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::svgbob::AMap;
+
+    fn round_trip(source: &str) {
+        let mut map = AMap::new(true);
+        let ascii = map.load_content(source);
+        let mermaid = ascii_to_mermaid(ascii.as_str());
+        assert_eq!(map.load_content(mermaid.as_str()), ascii, "{}", mermaid);
+    }
+
+    #[test]
+    fn test_read_boxes() {
+        let ascii = ".---.   +-------+\n| a |-->| 你 好 |\n'---'   +-------+\n\n.-----.\n| x y |\n'-----'\n";
+        let mut reader = AAsciiReader::new();
+        let result = reader.do_read(ascii);
+        assert_eq!(reader.boxes.len(), 3);
+        assert_eq!(reader.boxes[1].text, "你 好");
+        assert_eq!(result, "a --> n2[你 好]\nn3(x y)\n");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cases = [
+            "a",
+            "a[123]",
+            "aa ---> b",
+            "a-->b-->c --> d",
+            "aaa <--- b",
+            "aba ---v b",
+            "aca ---^ b",
+            "b <-- a --> c\n a --^ u\n a --v d",
+            "a --> b\na --> c\na --> d\nd --> f\nf --^ g --> h --^ k",
+            "a[x] --> b(中文) --v c[多行\\n文本]",
+            "a --- b",
+            "a --- b --> c",
+        ];
+        for source in cases {
+            round_trip(source);
+        }
+    }
+}
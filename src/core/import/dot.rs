@@ -0,0 +1,600 @@
+use std::collections::HashMap;
+
+use crate::core::diagram::ADiagnostic;
+use crate::core::svgbob::{ADirection, ASharp, Arrow, Cell, Direct};
+
+#[derive(Debug, Clone, PartialEq)]
+enum AToken {
+    // 标识符、数字或引号中的字符串
+    Id(String),
+    // -> 或 --
+    Edge(bool),
+    Punct(char),
+}
+
+// 带行号的 token，行号从 0 开始
+#[derive(Debug, Clone)]
+struct ASpanned {
+    token: AToken,
+    line: usize,
+}
+
+// dot 中的 subgraph cluster，目前只记录成员，渲染时不分组
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ACluster {
+    pub id: String,
+    pub label: String,
+    pub nodes: Vec<String>,
+}
+
+// 从 dot 解析出的图，节点按首次出现的顺序排列
+#[derive(Debug, Clone, Default)]
+pub struct ADotGraph {
+    pub directed: bool,
+    pub cells: Vec<Cell>,
+    pub edges: Vec<Arrow>,
+    pub clusters: Vec<ACluster>,
+    // rankdir 指定的方向，未指定时为空
+    pub direction: Option<ADirection>,
+}
+
+// 当前作用域中 node/edge 语句设置的默认属性
+#[derive(Debug, Clone, Default)]
+struct AScope {
+    node: HashMap<String, String>,
+    edge: HashMap<String, String>,
+    // 属于哪个 cluster
+    cluster: Option<usize>,
+}
+
+fn tokenize(content: &str) -> Result<Vec<ASpanned>, ADiagnostic> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut tokens: Vec<ASpanned> = Vec::new();
+    let mut line = 0;
+    let mut i = 0;
+    let error = |line: usize, message: &str| ADiagnostic {
+        line,
+        message: message.to_string(),
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied().unwrap_or(' ');
+        // 行首的 # 为预处理输出，当作注释
+        let line_start = tokens.last().is_none_or(|t| t.line != line);
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if (c == '/' && next == '/') || (c == '#' && line_start) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == '*' {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(error(line, "unterminated comment"));
+            }
+            i += 2;
+        } else if c == '-' && (next == '>' || next == '-') {
+            tokens.push(ASpanned {
+                token: AToken::Edge(next == '>'),
+                line,
+            });
+            i += 2;
+        } else if c == '"' {
+            let start = line;
+            let mut value = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                match (chars[i], chars.get(i + 1)) {
+                    ('\\', Some('"')) => value.push('"'),
+                    ('\\', Some('n')) | ('\\', Some('l')) | ('\\', Some('r')) => value.push('\n'),
+                    ('\\', Some('\n')) => line += 1,
+                    ('\\', Some(&v)) => {
+                        value.push('\\');
+                        value.push(v);
+                    }
+                    (v, _) => {
+                        if v == '\n' {
+                            line += 1;
+                        }
+                        value.push(v);
+                        i += 1;
+                        continue;
+                    }
+                }
+                i += 2;
+            }
+            if i >= chars.len() {
+                return Err(error(start, "unterminated string"));
+            }
+            i += 1;
+            tokens.push(ASpanned {
+                token: AToken::Id(value),
+                line: start,
+            });
+        } else if c == '<' {
+            // html 字符串只保留文字
+            let start = line;
+            let mut depth = 0;
+            let mut value = String::new();
+            let mut tag = false;
+            while i < chars.len() {
+                let v = chars[i];
+                match v {
+                    '<' => {
+                        depth += 1;
+                        tag = depth > 1;
+                    }
+                    '>' => {
+                        depth -= 1;
+                        tag = false;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    '\n' => line += 1,
+                    _ if !tag => value.push(v),
+                    _ => {}
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(error(start, "unterminated html string"));
+            }
+            i += 1;
+            tokens.push(ASpanned {
+                token: AToken::Id(value.trim().to_string()),
+                line: start,
+            });
+        } else if c.is_alphanumeric() || c == '_' || c == '.' || (c == '-' && next.is_ascii_digit())
+        {
+            let mut value = String::from(c);
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                value.push(chars[i]);
+                i += 1;
+            }
+            tokens.push(ASpanned {
+                token: AToken::Id(value),
+                line,
+            });
+        } else if "{}[];,=:".contains(c) {
+            tokens.push(ASpanned {
+                token: AToken::Punct(c),
+                line,
+            });
+            i += 1;
+        } else {
+            return Err(error(
+                line,
+                format!("unexpected character '{}'", c).as_str(),
+            ));
+        }
+    }
+    Ok(tokens)
+}
+
+// subgraph 最多嵌套的层数
+const MAX_DEPTH: usize = 64;
+
+// 解析 graphviz dot 语言的常用子集
+// 支持 graph/digraph、节点和连线语句、label/shape 属性以及 subgraph cluster
+// 解析出错时停止，已解析的部分仍然可以渲染
+#[derive(Debug, Default)]
+pub struct ADotParser {
+    tokens: Vec<ASpanned>,
+    pos: usize,
+    graph: ADotGraph,
+    // 节点 id 在 cells 中的位置
+    index: HashMap<String, usize>,
+    // 当前 subgraph 的嵌套层数
+    depth: usize,
+    pub errors: Vec<ADiagnostic>,
+}
+
+impl ADotParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn peek(&self) -> Option<&AToken> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or(self.tokens.last()) {
+            Some(v) => v.line,
+            None => 0,
+        }
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ADiagnostic> {
+        Err(ADiagnostic {
+            line: self.line(),
+            message,
+        })
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&AToken::Punct(c))
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ADiagnostic> {
+        if !self.is_punct(c) {
+            return self.error(format!("expected '{}'", c));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn id(&mut self) -> Result<String, ADiagnostic> {
+        match self.peek() {
+            Some(AToken::Id(v)) => {
+                let v = v.clone();
+                self.pos += 1;
+                Ok(v)
+            }
+            _ => self.error("expected identifier".to_string()),
+        }
+    }
+
+    // 关键字不区分大小写
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(AToken::Id(v)) if v.eq_ignore_ascii_case(keyword))
+    }
+
+    fn attr_list(&mut self) -> Result<HashMap<String, String>, ADiagnostic> {
+        let mut attrs = HashMap::new();
+        while self.is_punct('[') {
+            self.pos += 1;
+            while !self.is_punct(']') {
+                let key = self.id()?;
+                let value = match self.is_punct('=') {
+                    true => {
+                        self.pos += 1;
+                        self.id()?
+                    }
+                    false => "true".to_string(),
+                };
+                attrs.insert(key.to_lowercase(), value);
+                if self.is_punct(',') || self.is_punct(';') {
+                    self.pos += 1;
+                }
+            }
+            self.pos += 1;
+        }
+        Ok(attrs)
+    }
+
+    fn set_graph_attr(&mut self, key: &str, value: &str, scope: &AScope) {
+        match (key, scope.cluster) {
+            ("rankdir", None) => {
+                self.graph.direction = match value.to_uppercase().as_str() {
+                    "LR" | "RL" => Some(ADirection::LeftRight),
+                    _ => Some(ADirection::TopDown),
+                };
+            }
+            ("label", Some(i)) => self.graph.clusters[i].label = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn apply_node_attrs(&mut self, i: usize, attrs: &HashMap<String, String>) {
+        let cell = &mut self.graph.cells[i];
+        if let Some(label) = attrs.get("label") {
+            let mut node = Cell::new(cell.id.as_str(), label.as_str());
+            node.set_sharp(cell.sharp());
+            *cell = node;
+        }
+        if let Some(shape) = attrs.get("shape") {
+            let sharp = match shape.to_lowercase().as_str() {
+                "box" | "rect" | "rectangle" | "square" | "record" | "mrecord" => ASharp::Square,
+                "circle" | "doublecircle" => ASharp::Circle,
+                _ => ASharp::Round,
+            };
+            cell.set_sharp(sharp);
+        }
+    }
+
+    // 首次出现时创建节点并应用当前作用域的默认属性
+    fn node(&mut self, id: &str, scope: &AScope) -> usize {
+        let i = match self.index.get(id) {
+            Some(&v) => v,
+            None => {
+                let i = self.graph.cells.len();
+                self.graph.cells.push(Cell::new(id, id));
+                self.index.insert(id.to_string(), i);
+                self.apply_node_attrs(i, &scope.node);
+                i
+            }
+        };
+        if let Some(c) = scope.cluster {
+            let nodes = &mut self.graph.clusters[c].nodes;
+            if !nodes.iter().any(|v| v == id) {
+                nodes.push(id.to_string());
+            }
+        }
+        i
+    }
+
+    // 节点 id，忽略端口 a:port:n
+    fn node_id(&mut self) -> Result<String, ADiagnostic> {
+        let id = self.id()?;
+        while self.is_punct(':') {
+            self.pos += 1;
+            self.id()?;
+        }
+        Ok(id)
+    }
+
+    // 连线的一端，可以是节点或 subgraph，返回包含的节点
+    fn endpoint(&mut self, scope: &AScope) -> Result<Vec<String>, ADiagnostic> {
+        if self.is_keyword("subgraph") || self.is_punct('{') {
+            return self.subgraph(scope);
+        }
+        let id = self.node_id()?;
+        self.node(id.as_str(), scope);
+        Ok(vec![id])
+    }
+
+    fn subgraph(&mut self, parent: &AScope) -> Result<Vec<String>, ADiagnostic> {
+        let mut scope = parent.clone();
+        if self.is_keyword("subgraph") {
+            self.pos += 1;
+            if let Some(AToken::Id(_)) = self.peek() {
+                let id = self.id()?;
+                if id.starts_with("cluster") {
+                    self.graph.clusters.push(ACluster {
+                        id,
+                        ..Default::default()
+                    });
+                    scope.cluster = Some(self.graph.clusters.len() - 1);
+                }
+            }
+        }
+        // 嵌套过深时报错，避免递归导致栈溢出
+        if self.depth >= MAX_DEPTH {
+            return self.error(format!("subgraph nested deeper than {}", MAX_DEPTH));
+        }
+        let before = self.graph.cells.len();
+        let mut members: Vec<String> = Vec::new();
+        self.expect('{')?;
+        self.depth += 1;
+        self.stmt_list(&mut scope, &mut members)?;
+        self.depth -= 1;
+        self.expect('}')?;
+        // 新出现的节点同样属于该 subgraph
+        for cell in self.graph.cells[before..].iter() {
+            if !members.contains(&cell.id) {
+                members.push(cell.id.clone());
+            }
+        }
+        Ok(members)
+    }
+
+    fn stmt_list(
+        &mut self,
+        scope: &mut AScope,
+        members: &mut Vec<String>,
+    ) -> Result<(), ADiagnostic> {
+        while self.peek().is_some() && !self.is_punct('}') {
+            self.stmt(scope, members)?;
+            if self.is_punct(';') {
+                self.pos += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, scope: &mut AScope, members: &mut Vec<String>) -> Result<(), ADiagnostic> {
+        // graph/node/edge 默认属性
+        for keyword in ["graph", "node", "edge"] {
+            if self.is_keyword(keyword) {
+                self.pos += 1;
+                let attrs = self.attr_list()?;
+                match keyword {
+                    "node" => scope.node.extend(attrs),
+                    "edge" => scope.edge.extend(attrs),
+                    _ => {
+                        for (k, v) in attrs.iter() {
+                            self.set_graph_attr(k, v, scope);
+                        }
+                    }
+                }
+                return Ok(());
+            }
+        }
+        // a = b
+        if let (Some(AToken::Id(key)), Some(AToken::Punct('='))) = (
+            self.peek().cloned(),
+            self.tokens.get(self.pos + 1).map(|t| &t.token),
+        ) {
+            self.pos += 2;
+            let value = self.id()?;
+            self.set_graph_attr(key.to_lowercase().as_str(), value.as_str(), scope);
+            return Ok(());
+        }
+
+        let mut left = self.endpoint(scope)?;
+        members.extend(left.iter().cloned());
+        if !matches!(self.peek(), Some(AToken::Edge(_))) {
+            // 节点语句
+            let attrs = self.attr_list()?;
+            if let [id] = left.as_slice() {
+                let i = self.node(id, scope);
+                self.apply_node_attrs(i, &attrs);
+            }
+            return Ok(());
+        }
+
+        // 连线语句，a -> b -> c 或 {a b} -> c
+        let mut groups: Vec<Vec<String>> = vec![left.clone()];
+        while let Some(AToken::Edge(directed)) = self.peek().cloned() {
+            if directed != self.graph.directed {
+                let op = if self.graph.directed { "->" } else { "--" };
+                return self.error(format!("use '{}' for edges in this graph", op));
+            }
+            self.pos += 1;
+            left = self.endpoint(scope)?;
+            members.extend(left.iter().cloned());
+            groups.push(left.clone());
+        }
+        let mut attrs = scope.edge.clone();
+        attrs.extend(self.attr_list()?);
+        // 无向图默认没有箭头，dir 可以指定箭头方向
+        let direct = match attrs.get("dir").map(|v| v.as_str()) {
+            Some("forward") => Direct::Right,
+            Some("back") => Direct::Left,
+            Some("both") => Direct::Double,
+            Some("none") => Direct::None,
+            _ if self.graph.directed => Direct::Right,
+            _ => Direct::None,
+        };
+        let text = attrs.get("label").cloned().unwrap_or_default();
+        for pair in groups.windows(2) {
+            for src in pair[0].iter() {
+                for dst in pair[1].iter() {
+                    let edge = Arrow::new(direct.clone(), src.clone(), dst.clone(), text.clone());
+                    self.graph.edges.push(edge);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn graph(&mut self) -> Result<(), ADiagnostic> {
+        if self.is_keyword("strict") {
+            self.pos += 1;
+        }
+        self.graph.directed = match self.peek() {
+            Some(AToken::Id(v)) if v.eq_ignore_ascii_case("digraph") => true,
+            Some(AToken::Id(v)) if v.eq_ignore_ascii_case("graph") => false,
+            _ => return self.error("expected 'graph' or 'digraph'".to_string()),
+        };
+        self.pos += 1;
+        if let Some(AToken::Id(_)) = self.peek() {
+            self.pos += 1;
+        }
+        self.expect('{')?;
+        let mut scope = AScope::default();
+        let mut members: Vec<String> = Vec::new();
+        self.stmt_list(&mut scope, &mut members)?;
+        self.expect('}')?;
+        if self.peek().is_some() {
+            return self.error("unexpected content after graph".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn do_parse(&mut self, content: &str) -> ADotGraph {
+        self.pos = 0;
+        self.graph = ADotGraph::default();
+        self.index.clear();
+        self.depth = 0;
+        self.errors.clear();
+        match tokenize(content) {
+            Ok(v) => self.tokens = v,
+            Err(e) => {
+                self.errors.push(e);
+                return self.graph.clone();
+            }
+        }
+        if let Err(e) = self.graph() {
+            self.errors.push(e);
+        }
+        self.graph.clone()
+    }
+}
+
+// 首行是否为 dot，mermaid 的 graph TD 没有 {
+pub fn is_dot(first_line: &str) -> bool {
+    let mut words = first_line.split_whitespace();
+    let word = match words.next() {
+        Some(v) if v.eq_ignore_ascii_case("strict") => words.next().unwrap_or(""),
+        Some(v) => v,
+        None => return false,
+    };
+    word.eq_ignore_ascii_case("digraph")
+        || (word.eq_ignore_ascii_case("graph") && first_line.contains('{'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dot() {
+        let content = r#"digraph deps {
+    rankdir = LR;
+    node [shape=box];
+    // comment
+    app [label="App\nmain"];
+    app -> core -> utils [label="uses"];
+    subgraph cluster_gui {
+        label = "GUI";
+        node [shape=ellipse];
+        window; app -> window;
+    }
+    {cli lsp} -> core
+}"#;
+        let mut parser = ADotParser::new();
+        let graph = parser.do_parse(content);
+        assert!(parser.errors.is_empty(), "{:?}", parser.errors);
+        assert!(graph.directed);
+        assert_eq!(graph.direction, Some(ADirection::LeftRight));
+        let ids: Vec<&str> = graph.cells.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["app", "core", "utils", "window", "cli", "lsp"]);
+        assert_eq!(graph.cells[0].name, "App\nmain");
+        assert_eq!(graph.cells[0].sharp(), ASharp::Square);
+        assert_eq!(graph.cells[3].sharp(), ASharp::Round);
+        assert_eq!(graph.edges.len(), 5);
+        assert_eq!(graph.edges[1].text, "uses");
+        assert_eq!(graph.clusters[0].label, "GUI");
+        assert_eq!(graph.clusters[0].nodes, vec!["window", "app"]);
+
+        let graph = ADotParser::new().do_parse("graph { a -- b }");
+        assert!(!graph.directed);
+        assert_eq!(graph.edges[0].direct, Direct::None);
+        let options = crate::core::diagram::DiagramOptions::default();
+        let ascii = crate::core::diagram::render("graph { a -- b }", &options);
+        assert!(ascii.contains("| a |---| b |"), "{}", ascii);
+        let graph = ADotParser::new().do_parse("graph { a -- b [dir=forward] }");
+        assert_eq!(graph.edges[0].direct, Direct::Right);
+        let graph = ADotParser::new().do_parse("digraph { a -> b [dir=none] }");
+        assert_eq!(graph.edges[0].direct, Direct::None);
+
+        assert!(is_dot("strict digraph {"));
+        assert!(is_dot("graph G {"));
+        assert!(!is_dot("graph TD"));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let mut parser = ADotParser::new();
+        let graph = parser.do_parse("graph {\n a -- b\n b -> c\n}");
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(parser.errors[0].line, 2);
+        parser.do_parse("digraph {\n a [label=\"x]\n}");
+        assert_eq!(parser.errors[0].message, "unterminated string");
+        let nested = format!(
+            "graph {{\n{}a{}\n}}",
+            "{".repeat(200000),
+            "}".repeat(200000)
+        );
+        parser.do_parse(nested.as_str());
+        assert_eq!(parser.errors[0].message, "subgraph nested deeper than 64");
+        let nested = format!("graph {{\n{}a{}\n}}", "{".repeat(64), "}".repeat(64));
+        assert_eq!(parser.do_parse(nested.as_str()).cells.len(), 1);
+        assert!(parser.errors.is_empty());
+    }
+}
@@ -0,0 +1,5 @@
+mod ascii;
+mod dot;
+
+pub use ascii::{ascii_to_mermaid, AAsciiReader, ABox, ALink};
+pub use dot::{is_dot, ACluster, ADotGraph, ADotParser};
@@ -1,3 +1,13 @@
 pub mod adoc;
+pub mod chart;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod diagram;
+pub mod document;
+pub mod export;
+pub mod gitgraph;
+pub mod import;
 pub mod svgbob;
+pub mod timeline;
+pub mod tree;
 pub mod utils;
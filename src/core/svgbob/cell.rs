@@ -1,4 +1,6 @@
 use crate::core::utils::cn_length;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 use std::{fmt, ops::Not};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -19,20 +21,21 @@ pub enum Direct {
     // DownRight,
 }
 
-impl ToString for Direct {
-    fn to_string(&self) -> String {
-        match self {
-            Direct::None => String::from("none"),
-            Direct::Double => String::from("double"),
-            Direct::Left => String::from("left"),
-            Direct::Right => String::from("right"),
-            Direct::Up => String::from("up"),
-            Direct::Down => String::from("down"),
-            Direct::LeftUp => String::from("leftup"),
-            Direct::LeftDown => String::from("leftdown"),
-            Direct::RightUp => String::from("rightup"),
-            Direct::RightDown => String::from("rightdown"),
-        }
+impl fmt::Display for Direct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Direct::None => "none",
+            Direct::Double => "double",
+            Direct::Left => "left",
+            Direct::Right => "right",
+            Direct::Up => "up",
+            Direct::Down => "down",
+            Direct::LeftUp => "leftup",
+            Direct::LeftDown => "leftdown",
+            Direct::RightUp => "rightup",
+            Direct::RightDown => "rightdown",
+        };
+        write!(f, "{}", name)
     }
 }
 
@@ -55,17 +58,39 @@ impl Not for Direct {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-pub struct RBox {
-    pub w_left: usize,
-    pub w_right: usize,
-    pub h_up: usize,
-    pub h_down: usize,
-    pub left: Direct,
-    pub right: Direct,
-    pub up: Direct,
-    pub down: Direct,
-    pub left_down: Direct,
+// 流程图的布局方向，箭头 --> 在 TopDown 时向下排列
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ADirection {
+    #[default]
+    #[serde(rename = "LR", alias = "RL")]
+    LeftRight,
+    #[serde(rename = "TD", alias = "TB", alias = "BT")]
+    TopDown,
+}
+
+impl ADirection {
+    // mermaid 首行中的方向，例如 graph TD
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "LR" | "RL" => Some(ADirection::LeftRight),
+            "TD" | "TB" | "BT" => Some(ADirection::TopDown),
+            _ => None,
+        }
+    }
+
+    // 将按从左到右书写的箭头转换为当前方向
+    pub fn turn(&self, direct: Direct) -> Direct {
+        match self {
+            ADirection::LeftRight => direct,
+            ADirection::TopDown => match direct {
+                Direct::Right => Direct::Down,
+                Direct::Left => Direct::Up,
+                Direct::Down => Direct::Right,
+                Direct::Up => Direct::Left,
+                v => v,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -75,7 +100,18 @@ pub enum ASharp {
     Circle,
 }
 
-#[derive(Clone, Debug, Eq, Hash)]
+// 节点的链接，来自 mermaid 的 click 语句
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct ALink {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub tooltip: String,
+    // _blank 等打开方式
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq)]
 pub struct Cell {
     // 节点 id
     pub id: String,
@@ -92,6 +128,11 @@ pub struct Cell {
     pub arrows_no_render: Vec<Arrow>,
     // render 用形状
     sharp: ASharp,
+    // 文字左右两侧的空白
+    padding: usize,
+    // svgbob 的 css 标签，例如 {c0}，只在生成 svg 时写入 box 中
+    tag: Option<String>,
+    pub link: Option<ALink>,
 }
 
 impl Cell {
@@ -101,10 +142,10 @@ impl Cell {
         let nname: String = name.trim().to_string();
         let pwords: Vec<&str> = nname.split('\n').collect();
         let mut words = Vec::new();
-        let h: usize = pwords.len() as usize;
+        let h: usize = pwords.len();
         let mut w: usize = 0;
         for word in pwords {
-            w = std::cmp::max(w, cn_length(word) as usize);
+            w = std::cmp::max(w, cn_length(word));
             words.push(word.to_string());
         }
         Self {
@@ -116,6 +157,9 @@ impl Cell {
             arrows: Vec::new(),
             arrows_no_render: Vec::new(),
             sharp: ASharp::Round,
+            padding: 1,
+            tag: None,
+            link: None,
         }
     }
 
@@ -123,10 +167,31 @@ impl Cell {
         self.sharp = sharp;
     }
 
+    pub fn sharp(&self) -> ASharp {
+        self.sharp.clone()
+    }
+
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = padding;
+    }
+
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+    }
+
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    // 标签及其后的空格占用的宽度，右侧留出相同的宽度保持文字居中
+    fn tag_w(&self) -> usize {
+        self.tag.as_ref().map_or(0, |v| v.len() + 1)
+    }
+
     // 绘制，i 行数，maxw 最大宽度(含边框), emode 是否是扩展模式
     pub fn do_render(&self, i: usize, maxw: usize, emode: bool) -> String {
         let cw = maxw - 2;
-        let lb: usize = (cw - self.cw() + 1) / 2;
+        let lb: usize = (cw - self.cw()).div_ceil(2);
         let rb: usize = cw - self.cw() - lb;
 
         // 首行或者尾行
@@ -152,12 +217,23 @@ impl Cell {
         }
         // 超出行
         else if i >= self.h + 2 {
-            return format!("{}", " ".repeat(maxw));
+            return " ".repeat(maxw).to_string();
         }
-        // 内容行
+        // 内容行，标签写在首个内容行的左侧
+        let row = self.render_words(i, lb, rb, emode);
+        match (&self.tag, i) {
+            (Some(tag), 1) => {
+                let p = row.find('|').unwrap() + 1;
+                format!("{}{}{}", &row[..p], tag, &row[p + tag.len()..])
+            }
+            _ => row,
+        }
+    }
+
+    fn render_words(&self, i: usize, lb: usize, rb: usize, emode: bool) -> String {
         match self.words.get(i - 1) {
             Some(cword) => {
-                let lbank = (self.cw() - cn_length(cword) + 1) / 2;
+                let lbank = (self.cw() - cn_length(cword)).div_ceil(2);
                 let rbank = self.cw() - cn_length(cword) - lbank;
                 if emode {
                     let lstr = " ".repeat(lb + lbank);
@@ -166,39 +242,39 @@ impl Cell {
                 }
                 let lstr = " ".repeat(lbank);
                 let rstr = " ".repeat(rbank);
-                return format!(
+                format!(
                     "{}|{}{}{}|{}",
                     " ".repeat(lb),
                     lstr,
                     cword,
                     rstr,
                     " ".repeat(rb)
-                );
+                )
             }
             None => {
-                return format!(
+                format!(
                     "{}|{}|{}",
                     " ".repeat(lb),
                     " ".repeat(self.cw()),
                     " ".repeat(rb)
-                );
+                )
             }
         }
     }
 
     pub fn cw(&self) -> usize {
-        return self.w + 2;
+        self.w + self.padding * 2 + self.tag_w() * 2
     }
 
     pub fn total_w(&self) -> usize {
-        return self.cw() + 2;
+        self.cw() + 2
     }
 
     pub fn ch(&self) -> usize {
-        return self.h;
+        self.h
     }
     pub fn total_h(&self) -> usize {
-        return self.ch() + 2;
+        self.ch() + 2
     }
 }
 
@@ -214,6 +290,14 @@ impl PartialEq for Cell {
     }
 }
 
+// 与 PartialEq 保持一致，只使用 id 和 name
+impl Hash for Cell {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.name.hash(state);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Arrow {
     pub direct: Direct,
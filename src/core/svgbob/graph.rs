@@ -41,7 +41,7 @@ impl AGraph {
         if self.members.contains_key(id) {
             return true;
         }
-        return false;
+        false
     }
 
     pub fn add_member(&mut self, id: &String, cell: &Cell) {
@@ -175,7 +175,7 @@ impl AGraph {
         neg: bool,
     ) {
         let flag = match dir {
-            Direct::Right | Direct::Left => !neg,
+            Direct::Right | Direct::Left | Direct::None | Direct::Double => !neg,
             Direct::Up => neg,
             Direct::Down => !neg,
             _ => false,
@@ -186,7 +186,7 @@ impl AGraph {
         let edge = AEdgeCell::new(di.clone(), x, y, si.clone(), node.x, node.y, dir.clone());
 
         match dir {
-            Direct::Right | Direct::Left => {
+            Direct::Right | Direct::Left | Direct::None | Direct::Double => {
                 node.r_edges.push(edge);
             }
             Direct::Up | Direct::Down => {
@@ -212,7 +212,8 @@ impl AGraph {
         let x = self.nodes.get(src).unwrap().x;
         let y = self.nodes.get(src).unwrap().y;
         match dir {
-            Direct::Left | Direct::Right => {
+            // 没有箭头和双向箭头的连线与向右的连线一样横向排列
+            Direct::Left | Direct::Right | Direct::None | Direct::Double => {
                 if x == 0 && neg {
                     self.nodes_right();
                 }
@@ -295,7 +296,7 @@ impl AGraph {
         self.fit_wh();
     }
 
-    fn do_render_down_arrow(&self, i: usize, x: usize, y: usize, rbox: &Vec<RenderBox>) -> String {
+    fn do_render_down_arrow(&self, i: usize, x: usize, y: usize, rbox: &[RenderBox]) -> String {
         let mut content = String::new();
 
         let maxh = rbox.get(y).unwrap().down;
@@ -351,7 +352,7 @@ impl AGraph {
         _i: usize,
         x: usize,
         y: usize,
-        rbox: &Vec<RenderBox>,
+        rbox: &[RenderBox],
     ) -> String {
         let mut content = String::new();
         let maxw = rbox.get(x).unwrap().right;
@@ -381,7 +382,7 @@ impl AGraph {
         content
     }
 
-    fn render_edge_down(&self, y: usize, rbox: &Vec<RenderBox>) -> String {
+    fn render_edge_down(&self, y: usize, rbox: &[RenderBox]) -> String {
         let mut content = String::new();
         let maxh = rbox.get(y).unwrap().down;
 
@@ -389,7 +390,7 @@ impl AGraph {
             let mut line = String::new();
             for x in 0..self.w + 1 {
                 line.push_str(self.do_render_down_arrow(i, x, y, rbox).as_str());
-                line.push_str(&self.do_render_down_arrow_right(i, x, y, rbox).as_str());
+                line.push_str(self.do_render_down_arrow_right(i, x, y, rbox).as_str());
             }
             content.push_str(line.trim_end());
             content.push('\n');
@@ -398,7 +399,7 @@ impl AGraph {
         content
     }
 
-    fn do_render_cell(&self, i: usize, x: usize, y: usize, rbox: &Vec<RenderBox>) -> String {
+    fn do_render_cell(&self, i: usize, x: usize, y: usize, rbox: &[RenderBox]) -> String {
         let mut content = String::new();
         let maxw = rbox.get(x).unwrap().w;
         let cid = self.canvas.get(y).unwrap().get(x).unwrap();
@@ -411,13 +412,7 @@ impl AGraph {
         content
     }
 
-    fn inner_render_right_arrow(
-        &self,
-        i: usize,
-        x: usize,
-        y: usize,
-        rbox: &Vec<RenderBox>,
-    ) -> String {
+    fn inner_render_right_arrow(&self, i: usize, x: usize, y: usize, rbox: &[RenderBox]) -> String {
         let mut content = String::new();
 
         let maxh = rbox.get(y).unwrap().h;
@@ -426,7 +421,8 @@ impl AGraph {
         let bid = self.get_bid(x, y);
         let line = match self.rboard.get(&bid) {
             Some(v) => {
-                let mut adir = Direct::None;
+                // 结束于本行的连线的方向
+                let mut adir: Option<Direct> = None;
                 let mut is_over = false;
                 let mut adown = false;
                 let l: usize = (maxw - 1) / 2;
@@ -434,12 +430,8 @@ impl AGraph {
                 for ec in v.iter() {
                     // todo, 需要区分开
                     if ec.y == y {
-                        adir = ec.direct.clone();
-                        if ec.oy < y {
-                            adown = true;
-                        } else {
-                            adown = false;
-                        }
+                        adir = Some(ec.direct.clone());
+                        adown = ec.oy < y;
                     }
                     if ec.y > y {
                         is_over = true;
@@ -450,12 +442,16 @@ impl AGraph {
                 }
                 if i == maxh / 2 {
                     match adir {
-                        Direct::None => {
+                        None => {
                             format!("{}|{}", " ".repeat(l), " ".repeat(r - 1))
                         }
                         // 判断是否结束
-                        Direct::Left | Direct::Right => {
-                            let seq = if adir == Direct::Left { '<' } else { '>' };
+                        Some(Direct::Left | Direct::Right | Direct::None | Direct::Double) => {
+                            let seq = match adir {
+                                Some(Direct::Left) => '<',
+                                Some(Direct::None) => '-',
+                                _ => '>',
+                            };
                             if is_over {
                                 format!("{}+{}{}", " ".repeat(l), "-".repeat(r - 2), seq)
                             } else {
@@ -467,31 +463,25 @@ impl AGraph {
                         }
                     }
                 } else if i < maxh / 2 {
-                    if is_over {
-                        format!("{}|{}", " ".repeat(l), " ".repeat(r - 1))
-                    } else if adown && adir != Direct::None {
+                    if is_over || (adown && adir.is_some()) {
                         format!("{}|{}", " ".repeat(l), " ".repeat(r - 1))
                     } else {
                         " ".repeat(maxw)
                     }
+                } else if is_over || (!adown && adir.is_some()) {
+                    format!("{}|{}", " ".repeat(l), " ".repeat(r - 1))
                 } else {
-                    if is_over {
-                        format!("{}|{}", " ".repeat(l), " ".repeat(r - 1))
-                    } else if !adown && adir != Direct::None {
-                        format!("{}|{}", " ".repeat(l), " ".repeat(r - 1))
-                    } else {
-                        " ".repeat(maxw)
-                    }
+                    " ".repeat(maxw)
                 }
             }
             None => " ".repeat(maxw),
         };
 
         content.push_str(line.as_str());
-        return content;
+        content
     }
 
-    fn do_render_right_arrow(&self, i: usize, x: usize, y: usize, rbox: &Vec<RenderBox>) -> String {
+    fn do_render_right_arrow(&self, i: usize, x: usize, y: usize, rbox: &[RenderBox]) -> String {
         // 这里应该和 cell 一样，也是需要找到这个的最大宽度
         let mut content = String::new();
 
@@ -506,13 +496,13 @@ impl AGraph {
         let node = self.nodes.get(cid).unwrap();
 
         let udis = ((maxh - 1) / 2 - 1) / 2;
-        let ddis = ((maxh + 1) / 2 + 1) / 2;
+        let ddis = maxh.div_ceil(2).div_ceil(2);
         // 判断上节点
         if i == udis {
             // 右侧
             for ec in node.r_edges.iter() {
                 if ec.x > x && ec.y < y {
-                    content.push_str("-".repeat((maxw + 1) / 2).as_str());
+                    content.push_str("-".repeat(maxw.div_ceil(2)).as_str());
                     content.push('\'');
                     content.push_str(" ".repeat((maxw - 1) / 2).as_str());
                     break;
@@ -524,12 +514,21 @@ impl AGraph {
             // 右侧
             for ec in node.r_edges.iter() {
                 if ec.x > x && ec.y == y {
-                    if ec.direct == Direct::Left {
-                        content.push('<');
-                        content.push_str("-".repeat(maxw - 1).as_str());
-                    } else {
-                        content.push_str("-".repeat(maxw - 1).as_str());
-                        content.push('>');
+                    match ec.direct {
+                        Direct::Left => {
+                            content.push('<');
+                            content.push_str("-".repeat(maxw - 1).as_str());
+                        }
+                        Direct::None => content.push_str("-".repeat(maxw).as_str()),
+                        Direct::Double => {
+                            content.push('<');
+                            content.push_str("-".repeat(maxw - 2).as_str());
+                            content.push('>');
+                        }
+                        _ => {
+                            content.push_str("-".repeat(maxw - 1).as_str());
+                            content.push('>');
+                        }
                     }
                     break;
                 }
@@ -557,7 +556,7 @@ impl AGraph {
         content
     }
 
-    fn render_cell_with_edge(&self, y: usize, rbox: &Vec<RenderBox>) -> String {
+    fn render_cell_with_edge(&self, y: usize, rbox: &[RenderBox]) -> String {
         let mut content = String::new();
         let maxh = rbox.get(y).unwrap().h;
 
@@ -574,28 +573,19 @@ impl AGraph {
         content
     }
 
-    fn print_members(&self) {
-        println!("graph");
-        for (name, cell) in self.nodes.iter() {
-            println!("{}: ({}, {})", name, cell.x, cell.y);
-        }
-        println!("graph end!!!")
-    }
-
     // 绘制本graph
-    pub fn render(&self, rbox: &Vec<RenderBox>) -> String {
-        self.print_members();
+    pub fn render(&self, rbox: &[RenderBox]) -> String {
         // 绘制分为两个部分
         // 第一部分：绘制节点的上 edge 及上节点的下 edge
         // 第二部分：绘制节点和节点的左右 edge 部分
         let mut content = String::new();
         for y in 0..self.h + 1 {
-            let c_letters = self.render_cell_with_edge(y, &rbox);
-            let u_letters = self.render_edge_down(y, &rbox);
+            let c_letters = self.render_cell_with_edge(y, rbox);
+            let u_letters = self.render_edge_down(y, rbox);
 
             content.push_str(c_letters.trim_end());
             content.push('\n');
-            if u_letters.trim_end().len() > 0 {
+            if !u_letters.trim_end().is_empty() {
                 content.push_str(u_letters.trim_end());
                 content.push('\n');
             }
@@ -622,11 +612,8 @@ impl AGraph {
         for (id, node) in self.nodes.iter() {
             let x = node.x;
             let y = node.y;
-            match self.canvas.get_mut(y) {
-                Some(v) => {
-                    v[x] = id.clone();
-                }
-                None => {}
+            if let Some(v) = self.canvas.get_mut(y) {
+                v[x] = id.clone();
             }
         }
 
@@ -655,9 +642,7 @@ impl AGraph {
                                     v.push(ec.clone());
                                 }
                                 None => {
-                                    let mut array: Vec<AEdgeCell> = Vec::new();
-                                    array.push(ec.clone());
-                                    self.rboard.insert(bid, array);
+                                    self.rboard.insert(bid, vec![ec.clone()]);
                                 }
                             }
                         }
@@ -1,15 +1,19 @@
-use super::cell::{ASharp, Arrow, Cell, Direct};
+use super::cell::{ADirection, ALink, ASharp, Arrow, Cell, Direct};
 use super::graph::AGraph;
-use super::parse::{parse_edge, parse_node};
+use super::parse::{
+    parse_click, parse_edge, parse_node, parse_step, parse_style, split_classes, AStyle,
+};
 use std::cmp::{max, min};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Default, Copy)]
 pub struct RenderBox {
     pub w: usize,
+    #[allow(dead_code)]
     pub left: usize,
     pub right: usize,
     pub h: usize,
+    #[allow(dead_code)]
     pub up: usize,
     pub down: usize,
 }
@@ -18,6 +22,8 @@ pub struct RenderBox {
 pub struct AMap {
     // 记录所有 node 信息
     cells: HashMap<String, Cell>,
+    // node 首次出现的顺序，用于导出
+    order: Vec<String>,
     // 记录所有 edge 信息
     edges: Vec<Arrow>,
     // 以列表的形式来判断组
@@ -28,24 +34,146 @@ pub struct AMap {
     h: usize,
     // 是否扩展 box 保证相同
     expand_mode: bool,
+    // box 内文字两侧的空白
+    padding: usize,
+    direction: ADirection,
+    // classDef 定义的样式类
+    class_defs: Vec<(String, AStyle)>,
+    // class 语句及 a:::name 指定的 (节点, 样式类)
+    node_classes: Vec<(String, String)>,
+    // style 语句指定的节点样式
+    node_styles: Vec<(String, AStyle)>,
+    // click 语句指定的节点链接
+    links: Vec<(String, ALink)>,
+    // 有样式的节点写入 svgbob 的 css 标签，只用于生成 svg
+    css_tags: bool,
+    // 节点首次出现及连线所在的 (%% step 分组, 行序号)，用于分步显示
+    node_steps: HashMap<String, (usize, usize)>,
+    edge_steps: Vec<(usize, usize)>,
+    // 当前解析到的 (%% step 分组, 行序号)
+    step: (usize, usize),
+    // 出现过 %% step 时按分组显示，否则每行为一步
+    grouped: bool,
 }
 
 impl AMap {
     pub fn new(expand_mode: bool) -> Self {
         Self {
             cells: HashMap::new(),
+            order: Vec::new(),
             edges: Vec::new(),
             graphs: Vec::new(),
             w: 0,
             h: 0,
             expand_mode,
+            padding: 1,
+            direction: ADirection::LeftRight,
+            class_defs: Vec::new(),
+            node_classes: Vec::new(),
+            node_styles: Vec::new(),
+            links: Vec::new(),
+            css_tags: false,
+            node_steps: HashMap::new(),
+            edge_steps: Vec::new(),
+            step: (0, 0),
+            grouped: false,
         }
     }
 
+    pub fn set_css_tags(&mut self, css_tags: bool) {
+        self.css_tags = css_tags;
+    }
+
+    pub fn class_defs(&self) -> &[(String, AStyle)] {
+        &self.class_defs
+    }
+
+    pub fn node_classes(&self) -> &[(String, String)] {
+        &self.node_classes
+    }
+
+    pub fn node_styles(&self) -> &[(String, AStyle)] {
+        &self.node_styles
+    }
+
+    pub fn set_padding(&mut self, padding: usize) {
+        self.padding = padding;
+    }
+
+    pub fn set_direction(&mut self, direction: ADirection) {
+        self.direction = direction;
+    }
+
+    pub fn direction(&self) -> ADirection {
+        self.direction
+    }
+
+    // 按首次出现的顺序返回所有节点
+    pub fn cells(&self) -> Vec<&Cell> {
+        self.order
+            .iter()
+            .filter_map(|id| self.cells.get(id))
+            .collect()
+    }
+
+    pub fn edges(&self) -> &[Arrow] {
+        &self.edges
+    }
+
+    // 节点在布局中的位置 (graph, x, y)，x/y 为所在 graph 中的行列
+    pub fn position(&self, id: &str) -> Option<(usize, usize, usize)> {
+        self.graphs
+            .iter()
+            .enumerate()
+            .find_map(|(i, graph)| graph.nodes.get(id).map(|node| (i, node.x, node.y)))
+    }
+
+    // 分步显示时每一步新出现的 (节点, 连线序号)，连线在两端的节点出现之后才显示
+    pub fn steps(&self) -> Vec<(Vec<String>, Vec<usize>)> {
+        let key = |step: &(usize, usize)| if self.grouped { step.0 } else { step.1 };
+        let node_key = |id: &String| self.node_steps.get(id).map_or(0, key);
+        let edge_keys: Vec<usize> = self
+            .edges
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| {
+                let step = self.edge_steps.get(i).map_or(0, key);
+                step.max(node_key(&edge.src)).max(node_key(&edge.dst))
+            })
+            .collect();
+        let mut keys: Vec<usize> = self.order.iter().map(node_key).collect();
+        keys.extend(edge_keys.iter());
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .map(|k| {
+                let nodes = self
+                    .order
+                    .iter()
+                    .filter(|id| node_key(id) == k)
+                    .cloned()
+                    .collect();
+                let edges = (0..edge_keys.len())
+                    .filter(|&i| edge_keys[i] == k)
+                    .collect();
+                (nodes, edges)
+            })
+            .collect()
+    }
+
     fn clear(&mut self) {
         self.edges = Vec::new();
         self.cells = HashMap::new();
+        self.order = Vec::new();
         self.graphs = Vec::new();
+        self.class_defs = Vec::new();
+        self.node_classes = Vec::new();
+        self.node_styles = Vec::new();
+        self.links = Vec::new();
+        self.node_steps = HashMap::new();
+        self.edge_steps = Vec::new();
+        self.step = (0, 0);
+        self.grouped = false;
         self.w = 0;
         self.h = 0;
     }
@@ -59,11 +187,70 @@ impl AMap {
             .filter(|&s| !s.trim().is_empty())
             .collect();
         for line in lines.iter() {
+            // 注释中只处理 %% step N
+            if line.trim().starts_with("%%") {
+                if let Some(step) = parse_step(line) {
+                    self.step.0 = step;
+                    self.grouped = true;
+                }
+                continue;
+            }
             let aline = line.replace("\\n", "\n").replace("\t", " ");
+            if self.parse_statement(aline.as_str()) {
+                continue;
+            }
             self.parse_line(aline.as_str());
         }
     }
 
+    // 解析 style、classDef、class 和 linkStyle 语句，不是样式语句时返回 false
+    fn parse_statement(&mut self, line: &str) -> bool {
+        let line = line.trim().trim_end_matches(';');
+        // 链接中可能有 --
+        if line.contains("--") && !line.starts_with("click ") {
+            return false;
+        }
+        let (keyword, rest) = match line.split_once(char::is_whitespace) {
+            Some(v) => v,
+            None => return false,
+        };
+        let (target, value) = match rest.trim_start().split_once(char::is_whitespace) {
+            Some(v) => v,
+            None => return false,
+        };
+        let targets = target.split(',').map(|v| v.trim().to_string());
+        match keyword {
+            "style" => {
+                if let Some(style) = parse_style(value) {
+                    self.node_styles
+                        .extend(targets.map(|id| (id, style.clone())));
+                }
+            }
+            "classDef" => {
+                if let Some(style) = parse_style(value) {
+                    for name in targets {
+                        self.class_defs.retain(|(v, _)| *v != name);
+                        self.class_defs.push((name, style.clone()));
+                    }
+                }
+            }
+            "class" => {
+                let class = value.trim().to_string();
+                self.node_classes
+                    .extend(targets.map(|id| (id, class.clone())));
+            }
+            // svgbob 只能给封闭的图形加样式，连线的样式忽略，由诊断提示不支持
+            "linkStyle" => {}
+            "click" => {
+                if let Some(link) = parse_click(value) {
+                    self.links.push((target.trim().to_string(), link));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
     // 逐行解析出现的节点
     // 后续依据节点之间的关系重排节点位置
     fn parse_line<'a>(&'a mut self, line: &'a str) -> bool {
@@ -77,11 +264,16 @@ impl AMap {
         let mut name: &str;
         let mut sharp: ASharp;
         let mut a_text: String;
+        self.step.1 += 1;
 
         // 第一个 node
         (id, name, sharp, text) = parse_node(line);
+        let classes: Vec<&str>;
+        (id, name, text, classes) = split_classes(id, name, text);
+        self.add_classes(id, &classes);
         node = Cell::new(id, name);
         node.set_sharp(sharp);
+        node.set_padding(self.padding);
         lid = node.id.clone();
         self.add_node(&node);
         loop {
@@ -91,30 +283,132 @@ impl AMap {
             // edge
             (direct, a_text, vtext) = parse_edge(text);
             // node
-            if vtext.len() <= 0 {
+            if vtext.is_empty() {
                 break;
             }
             (id, name, sharp, text) = parse_node(vtext.as_str());
-            if id.len() == 0 {
+            let classes: Vec<&str>;
+            (id, name, text, classes) = split_classes(id, name, text);
+            if id.trim().is_empty() {
                 break;
             }
+            self.add_classes(id, &classes);
             node = Cell::new(id, name);
             node.set_sharp(sharp);
+            node.set_padding(self.padding);
             rid = node.id.clone();
             self.add_node(&node);
-            self.edges
-                .push(Arrow::new(direct, lid, rid.clone(), a_text));
+            self.edges.push(Arrow::new(
+                self.direction.turn(direct),
+                lid,
+                rid.clone(),
+                a_text,
+            ));
+            self.edge_steps.push(self.step);
             lid = rid;
         }
         true
     }
 
     // 将 node 加入到 graph 中
+    fn add_classes(&mut self, id: &str, classes: &[&str]) {
+        for class in classes {
+            self.node_classes
+                .push((id.trim().to_string(), class.to_string()));
+        }
+    }
+
+    // 节点使用的样式在图例中的序号，依次为 default 样式类、指定的样式类和 style 语句
+    fn style_indexes(&self, id: &str) -> Vec<usize> {
+        let class_index = |name: &str| self.class_defs.iter().position(|(v, _)| v == name);
+        let mut indexes: Vec<usize> = class_index("default").into_iter().collect();
+        for (nid, class) in self.node_classes.iter() {
+            if nid == id {
+                indexes.extend(class_index(class.as_str()));
+            }
+        }
+        for (i, (nid, _)) in self.node_styles.iter().enumerate() {
+            if nid == id {
+                indexes.push(self.class_defs.len() + i);
+            }
+        }
+        indexes.dedup();
+        indexes
+    }
+
+    // 有样式或链接的节点在 box 中写入 {c0,l0} 这样的标签，生成 svg 时成为图形的 css 类
+    fn assign_tags(&mut self) {
+        for (id, link) in self.links.iter() {
+            if let Some(cell) = self.cells.get_mut(id) {
+                cell.link = Some(link.clone());
+            }
+        }
+        let mut links = 0;
+        let mut tags: Vec<(String, Option<String>)> = Vec::new();
+        for id in self.order.iter() {
+            let mut names: Vec<String> = self
+                .style_indexes(id)
+                .iter()
+                .map(|i| format!("c{}", i))
+                .collect();
+            if self.cells.get(id).is_some_and(|c| c.link.is_some()) {
+                names.push(format!("l{}", links));
+                links += 1;
+            }
+            let tag = match self.css_tags && !names.is_empty() {
+                true => Some(format!("{{{}}}", names.join(","))),
+                false => None,
+            };
+            tags.push((id.clone(), tag));
+        }
+        for (id, tag) in tags {
+            if let Some(cell) = self.cells.get_mut(&id) {
+                cell.set_tag(tag);
+            }
+        }
+    }
+
+    // svgbob 的图例，定义标签对应的样式，svgbob 之外显示为普通文字
+    // 链接放在图例之后，svgbob 忽略这部分，生成 svg 之后再加上 <a>
+    fn legend(&self) -> String {
+        let styles = self
+            .class_defs
+            .iter()
+            .chain(self.node_styles.iter())
+            .map(|(_, style)| style);
+        let mut content = String::from("# Legend:\n");
+        for (i, style) in styles.enumerate() {
+            let css: Vec<String> = style
+                .iter()
+                .map(|(k, v)| format!("{}: {};", k, v))
+                .collect();
+            content.push_str(format!("c{} = {{{}}}\n", i, css.join(" ")).as_str());
+        }
+        let links: Vec<&ALink> = self
+            .cells()
+            .iter()
+            .filter_map(|c| c.link.as_ref())
+            .collect();
+        for i in 0..links.len() {
+            content.push_str(format!("l{} = {{cursor: pointer;}}\n", i).as_str());
+        }
+        if !links.is_empty() {
+            content.push_str("# Links:\n");
+        }
+        for (i, link) in links.into_iter().enumerate() {
+            let json = serde_json::to_string(link).unwrap();
+            content.push_str(format!("l{} = {}\n", i, json).as_str());
+        }
+        content
+    }
+
     fn add_node(&mut self, node: &Cell) -> bool {
         if self.cells.contains_key(&node.id) {
             return false;
         }
         self.cells.insert(node.id.clone(), node.clone());
+        self.order.push(node.id.clone());
+        self.node_steps.insert(node.id.clone(), self.step);
         true
     }
 
@@ -141,7 +435,6 @@ impl AMap {
             graph.add_member(did, dst);
             graph.add_edge(edge);
             self.graphs.push(graph);
-            return;
         }
         // dst 在
         else if slock == l {
@@ -193,11 +486,11 @@ impl AMap {
         for graph in self.graphs.iter() {
             for (_id, node) in graph.nodes.iter() {
                 for (i, cbox) in rboxes.iter_mut().enumerate() {
-                    if i == node.x as usize {
+                    if i == node.x {
                         cbox.w = max(cbox.w, node.w());
                         cbox.right = max(cbox.right, node.right());
                     }
-                    if i == node.y as usize {
+                    if i == node.y {
                         cbox.h = max(cbox.h, node.h());
                         cbox.down = max(cbox.down, node.down());
                     }
@@ -209,6 +502,7 @@ impl AMap {
 
     // 重排 nodes 之间的位置
     fn build_board(&mut self) {
+        self.assign_tags();
         let length = self.cells.len();
         self.graphs = Vec::with_capacity(length);
         // 添加集合体
@@ -235,22 +529,42 @@ impl AMap {
         }
     }
 
-    fn render(&self) -> String {
+    pub fn render(&self) -> String {
         let rbox: Vec<RenderBox> = self.build_render_box();
         let mut content = String::new();
         for graph in self.graphs.iter() {
             content.push_str(graph.render(&rbox).trim_end());
             content.push('\n');
         }
+        if self.css_tags && self.cells.values().any(|c| c.tag().is_some()) {
+            content.push_str(self.legend().as_str());
+        }
         content
     }
 
-    pub fn load_content(&mut self, content: &str) -> String {
+    // 只解析和排列节点，不生成文本
+    pub fn parse_content(&mut self, content: &str) {
         self.clear();
         self.build_cells(content);
         self.build_board();
-        println!("load content done.");
-        let content = self.render();
-        content
+    }
+
+    // 使用已经解析好的节点和连线，例如从 dot 导入，连线两端的节点必须存在
+    pub fn parse_graph(&mut self, cells: &[Cell], edges: &[Arrow]) {
+        self.clear();
+        for cell in cells.iter() {
+            let mut node = cell.clone();
+            node.set_padding(self.padding);
+            self.add_node(&node);
+        }
+        self.edges = edges.to_vec();
+        // 节点先全部显示，连线逐条显示
+        self.edge_steps = (1..=edges.len()).map(|i| (0, i)).collect();
+        self.build_board();
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.parse_content(content);
+        self.render()
     }
 }
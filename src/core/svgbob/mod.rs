@@ -5,4 +5,6 @@ mod node;
 mod parse;
 mod test;
 
+pub use cell::{ADirection, ALink, ASharp, Arrow, Cell, Direct};
 pub use maps::AMap;
+pub use parse::{parse_click, parse_step, AStyle};
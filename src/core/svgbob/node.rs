@@ -14,6 +14,7 @@ pub struct AEdgeCell {
     // dst id
     pub id: String,
     // origin id
+    #[allow(dead_code)]
     pub oid: String,
     // 方向
     pub direct: Direct,
@@ -51,7 +52,7 @@ impl AEdgeCell {
         if self.y == self.oy && max(self.x, self.ox) - min(self.x, self.ox) > 1 {
             return true;
         }
-        return false;
+        false
     }
 }
 
@@ -85,31 +86,29 @@ impl ANode {
     }
 
     pub fn w(&self) -> usize {
-        return self.cell.total_w();
+        self.cell.total_w()
     }
 
     pub fn right(&self) -> usize {
-        let w = match self.r_edges.len() {
+        match self.r_edges.len() {
             0 => 0,
             1 => 3,
             2 => 5,
             3 => 5,
             _ => 5,
-        };
-        return w;
+        }
     }
 
     pub fn down(&self) -> usize {
-        let w = match self.d_edges.len() {
+        match self.d_edges.len() {
             0 => 0,
             1 => 2,
             2 => 3,
             _ => 4,
-        };
-        return w;
+        }
     }
 
     pub fn h(&self) -> usize {
-        return self.cell.total_h();
+        self.cell.total_h()
     }
 }
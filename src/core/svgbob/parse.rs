@@ -1,10 +1,6 @@
-use super::cell::{Direct, ASharp};
+use super::cell::{ALink, ASharp, Direct};
 
-fn split_node_char<'a>(
-    input: &'a str,
-    l: char,
-    r: char,
-) -> Option<(&'a str, &'a str, ASharp, &'a str)> {
+fn split_node_char(input: &str, l: char, r: char) -> Option<(&str, &str, ASharp, &str)> {
     let sharp = match l {
         '(' => ASharp::Round,
         '[' => ASharp::Square,
@@ -12,50 +8,49 @@ fn split_node_char<'a>(
         _ => ASharp::Round,
     };
 
-    match input.find(l) {
-        Some(v) => match input.find(r) {
-            Some(vv) => {
-                let (node, r) = input.split_at(vv);
-                let (id, n) = node.split_at(v);
-                let (_, name) = n.split_at(1);
-                let (_, remain) = r.split_at(1);
-                return Some((id.trim(), name.trim(), sharp, remain.trim()));
-            }
-            None => {
-                let (id, n) = input.split_at(v);
-                let (_, name) = n.split_at(1);
-                return Some((id.trim(), name.trim(), sharp, ""));
-            }
-        },
+    // 括号必须属于第一个节点，例如 a --> b[x] 中的 [ 属于 b
+    let v = input.find(l)?;
+    if input[..v].contains(['-', '<', '>', '^']) {
+        return None;
+    }
+    match input.find(r) {
+        Some(vv) => {
+            let (node, r) = input.split_at(vv);
+            let (id, n) = node.split_at(v);
+            let (_, name) = n.split_at(1);
+            let (_, remain) = r.split_at(1);
+            Some((id.trim(), name.trim(), sharp, remain.trim()))
+        }
         None => {
-            return None;
+            let (id, n) = input.split_at(v);
+            let (_, name) = n.split_at(1);
+            Some((id.trim(), name.trim(), sharp, ""))
         }
     }
 }
 
 pub fn parse_node(input: &str) -> (&str, &str, ASharp, &str) {
-    match split_node_char(input, '(', ')') {
-        Some(v) => return v,
-        None => {}
+    if let Some(v) = split_node_char(input, '(', ')') {
+        return v;
     }
-    match split_node_char(input, '[', ']') {
-        Some(v) => return v,
-        None => {}
+    if let Some(v) = split_node_char(input, '[', ']') {
+        return v;
     }
-    match split_node_char(input, '{', '}') {
-        Some(v) => return v,
-        None => {}
+    if let Some(v) = split_node_char(input, '{', '}') {
+        return v;
     }
     let mut left: usize = 0;
-    for (i, c) in input.chars().enumerate() {
+    for (i, c) in input.char_indices() {
         if c == '-' || c == '<' || c == '>' || c == '^' {
             break;
         }
         left = i;
     }
+    // left 为字节位置，中文 id 需要按字符边界切分
+    let end = left + input[left..].chars().next().map_or(0, |c| c.len_utf8());
 
-    let (id, remain) = if left + 1 != input.len() {
-        input.split_at(left + 1)
+    let (id, remain) = if end != input.len() {
+        input.split_at(end)
     } else {
         (input, "")
     };
@@ -63,6 +58,129 @@ pub fn parse_node(input: &str) -> (&str, &str, ASharp, &str) {
     (id, id, ASharp::Round, remain)
 }
 
+// mermaid 样式中可以作用于 svgbob 图形的属性，文字颜色等其他属性忽略
+const SHAPE_STYLES: [&str; 7] = [
+    "fill",
+    "stroke",
+    "stroke-width",
+    "stroke-dasharray",
+    "opacity",
+    "fill-opacity",
+    "stroke-opacity",
+];
+
+// 样式属性列表，例如 [(fill, #f9f), (stroke, #333)]
+pub type AStyle = Vec<(String, String)>;
+
+// 解析 fill:#f9f,stroke:#333 这样的样式，没有可用的属性时返回 None
+pub fn parse_style(input: &str) -> Option<AStyle> {
+    let style: AStyle = input
+        .trim()
+        .trim_end_matches(';')
+        .split([',', ';'])
+        .filter_map(|v| v.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        // svgbob 的样式中不能出现花括号
+        .filter(|(k, v)| {
+            SHAPE_STYLES.contains(&k.as_str()) && !v.is_empty() && !v.contains(['{', '}'])
+        })
+        .collect();
+    match style.is_empty() {
+        true => None,
+        false => Some(style),
+    }
+}
+
+// 节点 a:::name 中的样式类，也可以写在括号之后，例如 a[x]:::name --> b
+// 返回去掉样式类之后的 id、name、剩余内容以及样式类
+pub fn split_classes<'a>(
+    id: &'a str,
+    name: &'a str,
+    remain: &'a str,
+) -> (&'a str, &'a str, &'a str, Vec<&'a str>) {
+    let mut classes = Vec::new();
+    let (mut nid, mut nname, mut nremain) = (id, name, remain);
+    if let Some((v, class)) = id.trim().split_once(":::") {
+        nid = v;
+        classes.push(class.trim());
+        if name == id {
+            nname = v;
+        }
+    }
+    if let Some(v) = remain.strip_prefix(":::") {
+        let end = v
+            .find(|c: char| c.is_whitespace() || "-<>^".contains(c))
+            .unwrap_or(v.len());
+        classes.push(&v[..end]);
+        nremain = v[end..].trim_start();
+    }
+    classes.retain(|v| !v.is_empty());
+    (nid, nname, nremain, classes)
+}
+
+// 按空白分开，引号中的内容为一项
+fn split_words(input: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut rest = input.trim();
+    while !rest.is_empty() {
+        let (word, remain) = match rest.strip_prefix('"') {
+            Some(v) => match v.find('"') {
+                Some(end) => (&v[..end], &v[end + 1..]),
+                None => (v, ""),
+            },
+            None => rest.split_at(rest.find(char::is_whitespace).unwrap_or(rest.len())),
+        };
+        words.push(word.to_string());
+        rest = remain.trim_start();
+    }
+    words
+}
+
+// 链接只允许 http、https、mailto 以及以 / # . 开头的相对地址
+// javascript: 等其他协议会在 svg 和 html 中执行脚本
+fn is_safe_url(url: &str) -> bool {
+    let scheme = url.split_once(':').map(|(v, _)| v).filter(|v| {
+        v.starts_with(|c: char| c.is_ascii_alphabetic())
+            && v.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    });
+    match scheme {
+        Some(v) => matches!(v.to_ascii_lowercase().as_str(), "http" | "https" | "mailto"),
+        None => url.starts_with(['/', '#', '.']),
+    }
+}
+
+// click 语句中 id 之后的部分，例如 "https://..." "tooltip" _blank 或者 href "url"
+// 回调函数和不安全的链接在 svg 中无法使用，返回 None
+pub fn parse_click(input: &str) -> Option<ALink> {
+    let mut words = split_words(input);
+    if words.first().map(|v| v.as_str()) == Some("href") {
+        words.remove(0);
+    }
+    let url = words.first()?.clone();
+    if !is_safe_url(url.as_str()) || url.contains(['<', '>']) {
+        return None;
+    }
+    let mut link = ALink {
+        url,
+        tooltip: String::new(),
+        target: None,
+    };
+    for word in words.into_iter().skip(1) {
+        match word.starts_with('_') && !word.contains(' ') {
+            true => link.target = Some(word),
+            false => link.tooltip = word,
+        }
+    }
+    Some(link)
+}
+
+// 分步显示的注释 %% step N
+pub fn parse_step(line: &str) -> Option<usize> {
+    let rest = line.trim().strip_prefix("%%")?.trim_start();
+    rest.strip_prefix("step")?.trim().parse().ok()
+}
+
 pub fn get_arrow(input: &str) -> Direct {
     if input.starts_with("<-") && input.ends_with("->") {
         return Direct::Double;
@@ -131,7 +249,7 @@ pub fn parse_edge(input: &str) -> (Direct, String, String) {
     };
     // TODO, parse arrow text
     let arrow = get_arrow(arrow.trim());
-    return (arrow, a_text, remain.to_string());
+    (arrow, a_text, remain.to_string())
 }
 
 #[cfg(test)]
@@ -149,12 +267,94 @@ mod tests {
             parse_node("a4[你好] cc"),
             ("a4", "你好", ASharp::Square, "cc")
         );
+        assert_eq!(
+            parse_node("a --> b[x]"),
+            ("a ", "a ", ASharp::Round, "--> b[x]")
+        );
+        assert_eq!(
+            parse_node("你好 --> b"),
+            ("你好 ", "你好 ", ASharp::Round, "--> b")
+        );
         assert_eq!(
             parse_node("天下[天下神一舞]"),
             ("天下", "天下神一舞", ASharp::Square, "")
         );
     }
 
+    // 括号只属于第一个节点以后，原有的输入解析和绘制结果不变
+    #[test]
+    fn test_node_parse_regression() {
+        assert_eq!(
+            parse_node("a[x] --> b"),
+            ("a", "x", ASharp::Square, "--> b")
+        );
+        assert_eq!(
+            parse_node("a(圆角) --> b"),
+            ("a", "圆角", ASharp::Round, "--> b")
+        );
+        assert_eq!(parse_node("a --> b"), ("a ", "a ", ASharp::Round, "--> b"));
+        assert_eq!(parse_node("ab<--c"), ("ab", "ab", ASharp::Round, "<--c"));
+
+        let cases = [
+            (
+                "天下[天下神一舞] --> b",
+                "+------------+   .---.\n| 天下神一舞 |-->| b |\n+------------+   '---'\n",
+            ),
+            (
+                "a --> b\na --> c",
+                ".---.     .---.\n| a |---->| b |\n'---'--.  '---'\n       |  .---.\n       '->| c |\n          '---'\n",
+            ),
+            (
+                "a{x} <-- b --^ c",
+                "        .---.\n        | c |\n        '---'\n          ^\n          |\n.---.   .---.\n| x |<--| b |\n'---'   '---'\n",
+            ),
+        ];
+        for (source, ascii) in cases {
+            let mut map = crate::core::svgbob::AMap::new(false);
+            assert_eq!(map.load_content(source).trim_end(), ascii.trim_end());
+        }
+    }
+
+    #[test]
+    fn test_style_parse() {
+        assert_eq!(
+            parse_style("fill:#f9f,stroke:#333,color:#fff;"),
+            Some(vec![
+                ("fill".to_string(), "#f9f".to_string()),
+                ("stroke".to_string(), "#333".to_string())
+            ])
+        );
+        assert_eq!(parse_style("color:red"), None);
+        let (id, name, _, remain) = parse_node("a:::hot --> b");
+        assert_eq!(
+            split_classes(id, name, remain),
+            ("a", "a", "--> b", vec!["hot"])
+        );
+        let (id, name, _, remain) = parse_node("a[x]:::hot --> b");
+        assert_eq!(
+            split_classes(id, name, remain),
+            ("a", "x", "--> b", vec!["hot"])
+        );
+    }
+
+    #[test]
+    fn test_click_parse() {
+        let link = parse_click("\"https://example.com/a b\" \"Service page\" _blank").unwrap();
+        assert_eq!(link.url, "https://example.com/a b");
+        assert_eq!(link.tooltip, "Service page");
+        assert_eq!(link.target.as_deref(), Some("_blank"));
+        assert_eq!(parse_click("href \"/docs\"").unwrap().url, "/docs");
+        assert!(parse_click("callback \"tooltip\"").is_none());
+        assert!(parse_click("\"javascript:alert(1)\"").is_none());
+        assert!(parse_click("\"javascript://%0aalert(document.domain)\" \"x\"").is_none());
+        assert!(parse_click("\"JaVaScRiPt:alert(1)\"").is_none());
+        assert!(parse_click("href \"data:text/html,x\"").is_none());
+        assert!(parse_click("\"HTTPS://example.com\"").is_some());
+        assert!(parse_click("\"mailto:a@example.com\"").is_some());
+        assert!(parse_click("\"#top\"").is_some());
+        assert!(parse_click("\"./a.html\"").is_some());
+    }
+
     #[test]
     fn test_arrow_parse() {
         // 只支持两种
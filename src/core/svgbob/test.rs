@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
 
-    use crate::core::svgbob::AMap;
+    use crate::core::svgbob::{ADirection, AMap};
 
     #[test]
     fn test_map_render() {
@@ -116,4 +116,22 @@ mod tests {
         );
         assert_eq!(gmap.load_content(code), result[1..]);
     }
+
+    #[test]
+    fn test_padding_direction() {
+        let mut gmap = AMap::new(false);
+        gmap.set_padding(2);
+        gmap.set_direction(ADirection::TopDown);
+        let result = "
+.-----.
+|  a  |
+'-----'
+   |
+   v
+.-----.
+|  b  |
+'-----'
+";
+        assert_eq!(gmap.load_content("a --> b"), result[1..]);
+    }
 }
@@ -0,0 +1,171 @@
+use super::section::{render_sections, split_sections};
+use crate::core::svgbob::Cell;
+use crate::core::utils::{cn_length, ACanvas};
+use std::cmp::max;
+
+// 相邻两个时间段之间的间隔
+const GAP: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct APeriod {
+    pub section: String,
+    pub name: String,
+    pub events: Vec<String>,
+}
+
+// mermaid timeline
+// timeline
+//     title xxx
+//     section xxx
+//     2002 : LinkedIn
+//     2004 : Facebook : Google
+//          : Youtube
+#[derive(Debug, Clone, Default)]
+pub struct ATimeline {
+    pub title: String,
+    pub periods: Vec<APeriod>,
+}
+
+fn split_events(input: &str) -> Vec<String> {
+    input
+        .split(':')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl ATimeline {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            periods: Vec::new(),
+        }
+    }
+
+    pub fn build_periods(&mut self, content: &str) {
+        self.title = String::new();
+        self.periods = Vec::new();
+        let mut section = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("%%") || line == "timeline" {
+                continue;
+            }
+            if let Some(v) = line.strip_prefix("title ") {
+                self.title = v.trim().to_string();
+            } else if let Some(v) = line.strip_prefix("section ") {
+                section = v.trim().to_string();
+            } else if let Some(v) = line.strip_prefix(':') {
+                // 续行，属于上一个时间段
+                if let Some(period) = self.periods.last_mut() {
+                    period.events.extend(split_events(v));
+                }
+            } else {
+                let (name, events) = line.split_once(':').unwrap_or((line, ""));
+                self.periods.push(APeriod {
+                    section: section.clone(),
+                    name: name.trim().to_string(),
+                    events: split_events(events),
+                });
+            }
+        }
+    }
+
+    pub fn render(&self) -> String {
+        if self.periods.is_empty() {
+            return String::new();
+        }
+        let cells: Vec<Cell> = self
+            .periods
+            .iter()
+            .map(|p| Cell::new(p.name.as_str(), p.name.as_str()))
+            .collect();
+        // 每一列的宽度和起始位置
+        let mut xs: Vec<usize> = Vec::new();
+        let mut ws: Vec<usize> = Vec::new();
+        let mut x = 2;
+        for (period, cell) in self.periods.iter().zip(cells.iter()) {
+            let ew = period
+                .events
+                .iter()
+                .map(|e| cn_length(e.as_str()))
+                .max()
+                .unwrap_or(0);
+            let w = max(cell.total_w(), ew);
+            xs.push(x);
+            ws.push(w);
+            x += w + GAP;
+        }
+
+        let mut canvas = ACanvas::new(0, 0);
+        let mut top = 0;
+        if !self.title.is_empty() {
+            canvas.text_center(0, x, 0, self.title.as_str());
+            top += 2;
+        }
+        let sections = split_sections(self.periods.iter().map(|p| p.section.as_str()));
+        top = render_sections(&mut canvas, &sections, &xs, &ws, top);
+
+        // 时间轴穿过每个时间段的方框
+        let mid = top + 1;
+        canvas.hline(0, x - GAP + 2, mid, '-');
+        canvas.put(x - GAP + 2, mid, '>');
+        for (i, period) in self.periods.iter().enumerate() {
+            let cell = &cells[i];
+            let bx = xs[i] + (ws[i] - cell.total_w()) / 2;
+            for r in 0..cell.total_h() {
+                canvas.text(
+                    bx,
+                    top + r,
+                    cell.do_render(r, cell.total_w(), true).as_str(),
+                );
+            }
+            if period.events.is_empty() {
+                continue;
+            }
+            let y = top + cell.total_h();
+            canvas.put(bx + cell.total_w() / 2, y, '|');
+            for (k, event) in period.events.iter().enumerate() {
+                canvas.text_center(xs[i], ws[i], y + 1 + k, event.as_str());
+            }
+        }
+        canvas.render()
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.build_periods(content);
+        self.render()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_render() {
+        let mut timeline = ATimeline::new();
+        let code = "timeline
+            section 早期
+            2002 : LinkedIn
+            2004 : Facebook : Google
+            section later
+            2005
+            ";
+        timeline.build_periods(code);
+        assert_eq!(timeline.periods.len(), 3);
+        assert_eq!(timeline.periods[1].events, vec!["Facebook", "Google"]);
+        assert_eq!(timeline.periods[2].section, "later");
+        let result = "
+         早期            later
+  +-----------------+   +------+
+  .------.   .------.   .------.
+--| 2002 |---| 2004 |---| 2005 |-->
+  '------'   '------'   '------'
+      |          |
+  LinkedIn   Facebook
+              Google
+";
+        assert_eq!(timeline.render(), result[1..]);
+    }
+}
@@ -0,0 +1,200 @@
+use super::section::{render_sections, split_sections};
+use crate::core::svgbob::Cell;
+use crate::core::utils::{cn_length, ACanvas};
+use std::cmp::max;
+
+// 相邻两个任务之间的间隔
+const GAP: usize = 2;
+// 满意度的最大分值
+const MAX_SCORE: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ATask {
+    pub section: String,
+    pub name: String,
+    pub score: usize,
+    pub actors: Vec<String>,
+}
+
+// mermaid journey
+// journey
+//     title xxx
+//     section xxx
+//       Make tea: 5: Me, Cat
+#[derive(Debug, Clone, Default)]
+pub struct AJourney {
+    pub title: String,
+    pub tasks: Vec<ATask>,
+}
+
+impl AJourney {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn build_tasks(&mut self, content: &str) {
+        self.title = String::new();
+        self.tasks = Vec::new();
+        let mut section = String::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("%%") || line == "journey" {
+                continue;
+            }
+            if let Some(v) = line.strip_prefix("title ") {
+                self.title = v.trim().to_string();
+                continue;
+            }
+            if let Some(v) = line.strip_prefix("section ") {
+                section = v.trim().to_string();
+                continue;
+            }
+            let parts: Vec<&str> = line.splitn(3, ':').collect();
+            if parts.len() < 2 {
+                continue;
+            }
+            let score: usize = match parts[1].trim().parse() {
+                Ok(v) => std::cmp::min(v, MAX_SCORE),
+                Err(_) => continue,
+            };
+            let actors = match parts.get(2) {
+                Some(v) => v
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                None => Vec::new(),
+            };
+            self.tasks.push(ATask {
+                section: section.clone(),
+                name: parts[0].trim().to_string(),
+                score,
+                actors,
+            });
+        }
+    }
+
+    pub fn render(&self) -> String {
+        if self.tasks.is_empty() {
+            return String::new();
+        }
+        let cells: Vec<Cell> = self
+            .tasks
+            .iter()
+            .map(|t| Cell::new(t.name.as_str(), t.name.as_str()))
+            .collect();
+        // 左侧留出分值刻度
+        let mut xs: Vec<usize> = Vec::new();
+        let mut ws: Vec<usize> = Vec::new();
+        let mut x = 4;
+        for (task, cell) in self.tasks.iter().zip(cells.iter()) {
+            let aw = task
+                .actors
+                .iter()
+                .map(|a| cn_length(a.as_str()))
+                .max()
+                .unwrap_or(0);
+            let w = max(cell.total_w(), aw);
+            xs.push(x);
+            ws.push(w);
+            x += w + GAP;
+        }
+
+        let mut canvas = ACanvas::new(0, 0);
+        let mut top = 0;
+        if !self.title.is_empty() {
+            canvas.text_center(0, x, 0, self.title.as_str());
+            top += 2;
+        }
+        let sections = split_sections(self.tasks.iter().map(|t| t.section.as_str()));
+        top = render_sections(&mut canvas, &sections, &xs, &ws, top);
+
+        let mut box_h = 0;
+        for (i, cell) in cells.iter().enumerate() {
+            let bx = xs[i] + (ws[i] - cell.total_w()) / 2;
+            for r in 0..cell.total_h() {
+                canvas.text(
+                    bx,
+                    top + r,
+                    cell.do_render(r, cell.total_w(), true).as_str(),
+                );
+            }
+            box_h = max(box_h, cell.total_h());
+        }
+
+        // 满意度折线，分值越高越靠上
+        let chart = top + box_h + 1;
+        let row = |score: usize| chart + MAX_SCORE - max(score, 1);
+        for score in 1..=MAX_SCORE {
+            canvas.text(0, row(score), score.to_string().as_str());
+            canvas.put(2, row(score), '+');
+        }
+        let points: Vec<(usize, usize)> = self
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (xs[i] + ws[i] / 2, row(t.score)))
+            .collect();
+        for p in points.windows(2) {
+            canvas.line(p[0].0, p[0].1, p[1].0, p[1].1);
+        }
+        for (px, py) in points.iter() {
+            canvas.put(*px, *py, '*');
+        }
+
+        // 参与者
+        let actors = row(1) + 2;
+        for (i, task) in self.tasks.iter().enumerate() {
+            for (k, actor) in task.actors.iter().enumerate() {
+                canvas.text_center(xs[i], ws[i], actors + k, actor.as_str());
+            }
+        }
+        canvas.render()
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.build_tasks(content);
+        self.render()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journey_render() {
+        let mut journey = AJourney::new();
+        let code = "journey
+            title Day
+            section Work
+              Make tea: 5: Me
+              Do work: 1: Me, Cat
+              bad line";
+        journey.build_tasks(code);
+        assert_eq!(journey.tasks.len(), 2);
+        assert_eq!(journey.tasks[1].actors, vec!["Me", "Cat"]);
+        let result = "
+              Day
+
+              Work
+    +-----------------------+
+    .----------.  .---------.
+    | Make tea |  | Do work |
+    '----------'  '---------'
+
+5 +       *-
+4 +         \\--
+3 +            \\--\\
+2 +                --\\
+1 +                   -*
+
+         Me           Me
+                      Cat
+";
+        assert_eq!(journey.render(), result[1..]);
+    }
+}
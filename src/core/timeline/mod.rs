@@ -0,0 +1,6 @@
+mod events;
+mod journey;
+mod section;
+
+pub use events::ATimeline;
+pub use journey::AJourney;
@@ -0,0 +1,63 @@
+use crate::core::utils::ACanvas;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ASection {
+    pub name: String,
+    // 包含的第一列和最后一列
+    pub start: usize,
+    pub end: usize,
+}
+
+// 将相邻的同名 section 合并，没有 section 的列不显示
+pub fn split_sections<'a>(names: impl Iterator<Item = &'a str>) -> Vec<ASection> {
+    let mut sections: Vec<ASection> = Vec::new();
+    for (i, name) in names.enumerate() {
+        if name.is_empty() {
+            continue;
+        }
+        match sections.last_mut() {
+            Some(v) if v.name == name && v.end + 1 == i => v.end = i,
+            _ => sections.push(ASection {
+                name: name.to_string(),
+                start: i,
+                end: i,
+            }),
+        }
+    }
+    sections
+}
+
+// 绘制 section 标题和下方的范围线，返回占用后的行号
+pub fn render_sections(
+    canvas: &mut ACanvas,
+    sections: &[ASection],
+    xs: &[usize],
+    ws: &[usize],
+    top: usize,
+) -> usize {
+    if sections.is_empty() {
+        return top;
+    }
+    for section in sections.iter() {
+        let l = xs[section.start];
+        let r = xs[section.end] + ws[section.end] - 1;
+        canvas.text_center(l, r - l + 1, top, section.name.as_str());
+        canvas.hline(l, r, top + 1, '-');
+        canvas.put(l, top + 1, '+');
+        canvas.put(r, top + 1, '+');
+    }
+    top + 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sections() {
+        let sections = split_sections(["a", "a", "", "b", "a"].into_iter());
+        assert_eq!(sections.len(), 3);
+        assert_eq!((sections[0].start, sections[0].end), (0, 1));
+        assert_eq!((sections[2].name.as_str(), sections[2].start), ("a", 4));
+    }
+}
@@ -0,0 +1,355 @@
+use super::outline::{parse_mindmap_line, parse_outline_line, AOutlineLine};
+use crate::core::svgbob::Cell;
+use crate::core::utils::ACanvas;
+use std::cmp::max;
+use std::ops::Range;
+
+// 父子节点之间连线占用的宽度
+const GAP: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ATreeMode {
+    // 从左到右展开的树
+    Tree,
+    // 根节点居中，子节点分布在两侧
+    Mindmap,
+}
+
+#[derive(Debug, Clone)]
+pub struct ATreeNode {
+    pub cell: Cell,
+    pub children: Vec<usize>,
+    pub depth: usize,
+    // 是否绘制在根节点左侧
+    pub left: bool,
+    pub x: usize,
+    pub y: usize,
+}
+
+impl ATreeNode {
+    fn new(cell: Cell, depth: usize) -> Self {
+        Self {
+            cell,
+            children: Vec::new(),
+            depth,
+            left: false,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    fn w(&self) -> usize {
+        self.cell.total_w()
+    }
+
+    fn h(&self) -> usize {
+        self.cell.total_h()
+    }
+
+    // 连线所在的行(相对位置)
+    fn mid(&self) -> usize {
+        self.h() / 2
+    }
+}
+
+// 由缩进描述的层级结构，例如 mermaid mindmap、目录树、大纲
+#[derive(Debug, Clone)]
+pub struct ATree {
+    pub mode: ATreeMode,
+    pub nodes: Vec<ATreeNode>,
+    pub roots: Vec<usize>,
+}
+
+impl ATree {
+    pub fn new(mode: ATreeMode) -> Self {
+        Self {
+            mode,
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.nodes = Vec::new();
+        self.roots = Vec::new();
+    }
+
+    // 依据缩进建立层级，父节点为前面最近的缩进更小的节点
+    pub fn build_nodes(&mut self, content: &str) {
+        self.clear();
+        let mut lines = content.lines().filter(|s| !s.trim().is_empty()).peekable();
+        if let Some(first) = lines.peek() {
+            match first.trim() {
+                "mindmap" => {
+                    self.mode = ATreeMode::Mindmap;
+                    lines.next();
+                }
+                "tree" => {
+                    self.mode = ATreeMode::Tree;
+                    lines.next();
+                }
+                _ => {}
+            }
+        }
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        for line in lines {
+            let parsed: Option<AOutlineLine> = match self.mode {
+                ATreeMode::Mindmap => parse_mindmap_line(line),
+                ATreeMode::Tree => parse_outline_line(line),
+            };
+            let line = match parsed {
+                Some(v) => v,
+                None => continue,
+            };
+            while let Some(&(indent, _)) = stack.last() {
+                if indent < line.indent {
+                    break;
+                }
+                stack.pop();
+            }
+            let id = self.nodes.len();
+            let mut cell = Cell::new(id.to_string().as_str(), line.text.as_str());
+            cell.set_sharp(line.sharp);
+            match stack.last() {
+                Some(&(_, parent)) => {
+                    let depth = self.nodes[parent].depth + 1;
+                    self.nodes.push(ATreeNode::new(cell, depth));
+                    self.nodes[parent].children.push(id);
+                }
+                None => {
+                    self.nodes.push(ATreeNode::new(cell, 0));
+                    self.roots.push(id);
+                }
+            }
+            stack.push((line.indent, id));
+        }
+    }
+
+    fn mark_left(&mut self, id: usize) {
+        self.nodes[id].left = true;
+        for c in self.nodes[id].children.clone() {
+            self.mark_left(c);
+        }
+    }
+
+    fn shift(&mut self, id: usize, dy: usize) {
+        self.nodes[id].y += dy;
+        for c in self.nodes[id].children.clone() {
+            self.shift(c, dy);
+        }
+    }
+
+    // 从 top 开始依次排列子节点，返回 (占用的底部行, 父节点 y)
+    // 父节点与第一个子节点在同一行连接
+    fn place_group(&mut self, children: &[usize], top: usize, pm: usize) -> (usize, usize) {
+        let c0m = self.nodes[children[0]].mid();
+        let ctop = top + pm.saturating_sub(c0m);
+        let mut bottom = ctop;
+        for c in children.iter() {
+            bottom = self.place(*c, bottom);
+        }
+        (bottom, ctop + c0m - pm)
+    }
+
+    fn place(&mut self, id: usize, top: usize) -> usize {
+        let children = self.nodes[id].children.clone();
+        let (h, m) = (self.nodes[id].h(), self.nodes[id].mid());
+        if children.is_empty() {
+            self.nodes[id].y = top;
+            return top + h + 1;
+        }
+        let (bottom, y) = self.place_group(&children, top, m);
+        self.nodes[id].y = y;
+        max(bottom, y + h + 1)
+    }
+
+    // 思维导图的根节点，两侧子节点分别排列后对齐
+    fn place_root(&mut self, id: usize, top: usize) -> usize {
+        let children = self.nodes[id].children.clone();
+        if self.mode == ATreeMode::Tree || children.len() < 2 {
+            return self.place(id, top);
+        }
+        let (right, left) = children.split_at(children.len().div_ceil(2));
+        for c in left.iter() {
+            self.mark_left(*c);
+        }
+        let (h, m) = (self.nodes[id].h(), self.nodes[id].mid());
+        let (rb, ry) = self.place_group(right, top, m);
+        let (lb, ly) = self.place_group(left, top, m);
+        let y = max(ry, ly);
+        for c in right.iter() {
+            self.shift(*c, y - ry);
+        }
+        for c in left.iter() {
+            self.shift(*c, y - ly);
+        }
+        self.nodes[id].y = y;
+        max(max(rb + y - ry, lb + y - ly), y + h + 1)
+    }
+
+    // 根节点及其子孙节点的 id 范围，节点是按先序创建的
+    fn subtree(&self, root: usize) -> Range<usize> {
+        let end = self
+            .roots
+            .iter()
+            .find(|&&r| r > root)
+            .copied()
+            .unwrap_or(self.nodes.len());
+        root..end
+    }
+
+    // 计算每一层的最大宽度
+    fn column_widths(&self, root: usize, left: bool) -> Vec<usize> {
+        let mut widths: Vec<usize> = Vec::new();
+        for node in self.nodes[self.subtree(root)].iter() {
+            if node.depth == 0 || node.left != left {
+                continue;
+            }
+            if widths.len() < node.depth {
+                widths.resize(node.depth, 0);
+            }
+            widths[node.depth - 1] = max(widths[node.depth - 1], node.w());
+        }
+        widths
+    }
+
+    // 第 depth 层子节点所在列，右侧返回左边界，左侧返回右边界(不含)
+    fn column(&self, root: usize, depth: usize, left: bool) -> usize {
+        let rx = self.nodes[root].x;
+        let widths = self.column_widths(root, left);
+        let offset: usize = widths[..depth - 1].iter().map(|w| w + GAP).sum();
+        if left {
+            rx - GAP - offset
+        } else {
+            rx + self.nodes[root].w() + GAP + offset
+        }
+    }
+
+    fn assign_x(&mut self, root: usize) {
+        let lws = self.column_widths(root, true);
+        self.nodes[root].x = lws.iter().map(|w| w + GAP).sum::<usize>();
+        for id in self.subtree(root).skip(1) {
+            let (depth, left) = (self.nodes[id].depth, self.nodes[id].left);
+            let col = self.column(root, depth, left);
+            self.nodes[id].x = if left { col - self.nodes[id].w() } else { col };
+        }
+    }
+
+    fn render_cell(&self, canvas: &mut ACanvas, node: &ATreeNode) {
+        for i in 0..node.h() {
+            canvas.text(
+                node.x,
+                node.y + i,
+                node.cell.do_render(i, node.w(), true).as_str(),
+            );
+        }
+    }
+
+    // 绘制父节点与子节点之间的肘形连线
+    fn render_edges(&self, canvas: &mut ACanvas, root: usize, id: usize, left: bool) {
+        let node = &self.nodes[id];
+        let children: Vec<usize> = node
+            .children
+            .iter()
+            .filter(|&&c| self.nodes[c].left == left)
+            .copied()
+            .collect();
+        if children.is_empty() {
+            return;
+        }
+        let py = node.y + node.mid();
+        // 主干位于两列之间的空隙中间
+        let col = self.column(root, node.depth + 1, left);
+        let tx = if left {
+            col + GAP / 2
+        } else {
+            col - GAP.div_ceil(2)
+        };
+        if left {
+            canvas.hline(tx, node.x - 1, py, '-');
+        } else {
+            canvas.hline(node.x + node.w(), tx, py, '-');
+        }
+        let last = children.len() - 1;
+        for (i, c) in children.iter().enumerate() {
+            let child = &self.nodes[*c];
+            let cy = child.y + child.mid();
+            if left {
+                canvas.hline(child.x + child.w(), tx, cy, '-');
+            } else {
+                canvas.hline(tx, child.x - 1, cy, '-');
+            }
+            if i > 0 {
+                let prev = &self.nodes[children[i - 1]];
+                canvas.vline(tx, prev.y + prev.mid() + 1, cy - 1, '|');
+            }
+            let c = if i == last && i > 0 {
+                '\''
+            } else if last > 0 {
+                '+'
+            } else {
+                '-'
+            };
+            canvas.put(tx, cy, c);
+        }
+        for c in children.iter() {
+            self.render_edges(canvas, root, *c, left);
+        }
+    }
+
+    pub fn render(&mut self) -> String {
+        let mut canvas = ACanvas::new(0, 0);
+        let mut top = 0;
+        for root in self.roots.clone() {
+            top = self.place_root(root, top);
+            self.assign_x(root);
+            self.render_edges(&mut canvas, root, root, false);
+            self.render_edges(&mut canvas, root, root, true);
+            for node in self.nodes[self.subtree(root)].iter() {
+                self.render_cell(&mut canvas, node);
+            }
+        }
+        canvas.render()
+    }
+
+    pub fn load_content(&mut self, content: &str) -> String {
+        self.build_nodes(content);
+        self.render()
+    }
+}
+
+impl Default for ATree {
+    fn default() -> Self {
+        Self::new(ATreeMode::Tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_build() {
+        let mut tree = ATree::new(ATreeMode::Tree);
+        tree.build_nodes("a\n  b\n    c\n  d\ne");
+        assert_eq!(tree.roots, vec![0, 4]);
+        assert_eq!(tree.nodes[0].children, vec![1, 3]);
+        assert_eq!(tree.nodes[1].children, vec![2]);
+        assert_eq!(tree.nodes[2].depth, 2);
+    }
+
+    #[test]
+    fn test_tree_render() {
+        let mut tree = ATree::new(ATreeMode::Tree);
+        let result = "
+.------.     .---.
+| root |--+--| a |
+'------'  |  '---'
+          |
+          |  .---.
+          '--| b |
+             '---'
+";
+        assert_eq!(tree.load_content("root\n  a\n  b"), result[1..]);
+    }
+}
@@ -0,0 +1,4 @@
+mod layout;
+mod outline;
+
+pub use layout::{ATree, ATreeMode};
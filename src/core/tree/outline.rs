@@ -0,0 +1,143 @@
+use crate::core::svgbob::ASharp;
+
+// 目录树中的连线符号
+const TREE_PREFIX: &[char] = &['│', '├', '└', '─', '┬', '┼', '|', ' ', '\t'];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AOutlineLine {
+    // 缩进层级，按字符数计算
+    pub indent: usize,
+    pub text: String,
+    pub sharp: ASharp,
+}
+
+// 计算缩进，tab 按 4 个空格算
+fn indent_of(prefix: &str) -> usize {
+    prefix.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+// tree 命令最后的统计信息
+fn is_tree_summary(line: &str) -> bool {
+    (line.contains("directories,") || line.contains("directory,"))
+        && (line.ends_with("files") || line.ends_with("file"))
+}
+
+// 去掉连线符号、tree --charset ascii 的 `-- 和 |-- 以及最多一个列表符号，
+// 文字本身以 - + * 开头时保留，例如 --verbose、*ptr
+fn strip_tree_prefix(line: &str) -> &str {
+    let mut text = line.trim_start_matches(TREE_PREFIX);
+    let connector = text.trim_start_matches(['`', '+']);
+    if connector.starts_with("--") {
+        if let Some(v) = connector.trim_start_matches('-').strip_prefix(' ') {
+            text = v.trim_start_matches(TREE_PREFIX);
+        }
+    }
+    for marker in ["- ", "+ ", "* "] {
+        if let Some(v) = text.strip_prefix(marker) {
+            return v.trim_start_matches(TREE_PREFIX);
+        }
+    }
+    text
+}
+
+// 解析普通缩进文本或者目录树的一行
+pub fn parse_outline_line(line: &str) -> Option<AOutlineLine> {
+    let text = strip_tree_prefix(line).trim_end();
+    if text.is_empty() || is_tree_summary(text) {
+        return None;
+    }
+    let prefix = &line[..line.len() - strip_tree_prefix(line).len()];
+    Some(AOutlineLine {
+        indent: indent_of(prefix),
+        text: text.to_string(),
+        sharp: ASharp::Round,
+    })
+}
+
+// 解析 mermaid mindmap 的节点形状
+// id((text)) id(text) id[text] id{{text}} id))text(( id)text(
+fn parse_mind_node(input: &str) -> (String, ASharp) {
+    let pairs = [
+        ("((", "))", ASharp::Circle),
+        ("{{", "}}", ASharp::Square),
+        ("))", "((", ASharp::Round),
+        ("[", "]", ASharp::Square),
+        ("(", ")", ASharp::Round),
+        (")", "(", ASharp::Round),
+    ];
+    for (l, r, sharp) in pairs {
+        if !input.ends_with(r) {
+            continue;
+        }
+        if let Some(i) = input.find(l) {
+            let end = input.len() - r.len();
+            if i + l.len() <= end {
+                return (input[i + l.len()..end].to_string(), sharp);
+            }
+        }
+    }
+    (input.to_string(), ASharp::Round)
+}
+
+fn clean_text(input: &str) -> String {
+    input
+        .trim()
+        .trim_matches('"')
+        .trim_matches('`')
+        .replace("<br/>", "\n")
+        .replace("<br>", "\n")
+        .replace("\\n", "\n")
+}
+
+// 解析 mermaid mindmap 的一行，忽略 icon 和 class
+pub fn parse_mindmap_line(line: &str) -> Option<AOutlineLine> {
+    let text = line.trim();
+    if text.is_empty() || text.starts_with("::icon") || text.starts_with("%%") {
+        return None;
+    }
+    let text = match text.find(":::") {
+        Some(i) => text[..i].trim(),
+        None => text,
+    };
+    let (name, sharp) = parse_mind_node(text);
+    Some(AOutlineLine {
+        indent: indent_of(&line[..line.len() - line.trim_start().len()]),
+        text: clean_text(name.as_str()),
+        sharp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_parse() {
+        let line = parse_outline_line("│   ├── main.rs").unwrap();
+        assert_eq!((line.indent, line.text.as_str()), (8, "main.rs"));
+        let line = parse_outline_line("  - item").unwrap();
+        assert_eq!((line.indent, line.text.as_str()), (4, "item"));
+        assert_eq!(parse_outline_line("3 directories, 5 files"), None);
+        assert_eq!(parse_outline_line("│"), None);
+        // 只去掉一个列表符号，文字中的 - * 保留
+        let line = parse_outline_line("│   ├── --verbose").unwrap();
+        assert_eq!((line.indent, line.text.as_str()), (8, "--verbose"));
+        assert_eq!(parse_outline_line("  * *ptr").unwrap().text, "*ptr");
+        assert_eq!(parse_outline_line("- - item").unwrap().text, "- item");
+        assert_eq!(parse_outline_line("+x").unwrap().text, "+x");
+        let line = parse_outline_line("|   `-- main.rs").unwrap();
+        assert_eq!((line.indent, line.text.as_str()), (8, "main.rs"));
+    }
+
+    #[test]
+    fn test_mindmap_parse() {
+        let line = parse_mindmap_line("  root((mind map))").unwrap();
+        assert_eq!(line.text, "mind map");
+        assert_eq!(line.sharp, ASharp::Circle);
+        let line = parse_mindmap_line("    b[方形]:::urgent").unwrap();
+        assert_eq!((line.indent, line.text.as_str()), (4, "方形"));
+        assert_eq!(line.sharp, ASharp::Square);
+        assert_eq!(parse_mindmap_line("a<br/>b").unwrap().text, "a\nb");
+        assert_eq!(parse_mindmap_line("    ::icon(fa fa-book)"), None);
+    }
+}
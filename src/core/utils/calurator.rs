@@ -4,6 +4,14 @@ pub fn cn_length(input: &str) -> usize {
     (a + b) / 2
 }
 
+// 转义 xml 属性和文字内容，svg、html 和 draw.io 共用
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -14,5 +22,9 @@ mod tests {
         assert_eq!(cn_length("你好"), 4);
         assert_eq!(cn_length("你好hah"), 7);
         assert_eq!(cn_length("？。hah"), 7);
+        assert_eq!(
+            escape("<a href=\"?a&b\">"),
+            "&lt;a href=&quot;?a&amp;b&quot;&gt;"
+        );
     }
 }
@@ -0,0 +1,170 @@
+use super::cn_length;
+
+// 宽字符占两个格子，第二个格子用占位符填充，输出时跳过
+const WIDE_HOLDER: char = '\0';
+
+// 以字符为单位的画布，用于绘制 svgbob 风格的 ascii 图
+#[derive(Debug, Clone)]
+pub struct ACanvas {
+    pub w: usize,
+    pub h: usize,
+    rows: Vec<Vec<char>>,
+}
+
+impl ACanvas {
+    pub fn new(w: usize, h: usize) -> Self {
+        Self {
+            w,
+            h,
+            rows: vec![vec![' '; w]; h],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> char {
+        match self.rows.get(y).and_then(|r| r.get(x)) {
+            Some(c) => *c,
+            None => ' ',
+        }
+    }
+
+    pub fn is_blank(&self, x: usize, y: usize) -> bool {
+        self.get(x, y) == ' '
+    }
+
+    // 超出范围时自动扩展画布
+    pub fn put(&mut self, x: usize, y: usize, c: char) {
+        if y >= self.h {
+            self.rows.resize(y + 1, vec![' '; self.w]);
+            self.h = y + 1;
+        }
+        if x >= self.w {
+            for row in self.rows.iter_mut() {
+                row.resize(x + 1, ' ');
+            }
+            self.w = x + 1;
+        }
+        self.rows[y][x] = c;
+    }
+
+    // 仅在空白处绘制，避免覆盖已有内容
+    pub fn put_blank(&mut self, x: usize, y: usize, c: char) {
+        if self.is_blank(x, y) {
+            self.put(x, y, c);
+        }
+    }
+
+    // 写入文本，返回文本结束后的横坐标
+    pub fn text(&mut self, x: usize, y: usize, s: &str) -> usize {
+        let mut cx = x;
+        for c in s.chars() {
+            let cw = cn_length(c.to_string().as_str());
+            self.put(cx, y, c);
+            if cw > 1 {
+                self.put(cx + 1, y, WIDE_HOLDER);
+            }
+            cx += cw;
+        }
+        cx
+    }
+
+    // 在 [x, x + w) 范围内居中写入文本
+    pub fn text_center(&mut self, x: usize, w: usize, y: usize, s: &str) -> usize {
+        let tw = cn_length(s);
+        self.text(x + w.saturating_sub(tw) / 2, y, s)
+    }
+
+    pub fn hline(&mut self, x1: usize, x2: usize, y: usize, c: char) {
+        for x in x1..=x2 {
+            self.put(x, y, c);
+        }
+    }
+
+    pub fn vline(&mut self, x: usize, y1: usize, y2: usize, c: char) {
+        for y in y1..=y2 {
+            self.put(x, y, c);
+        }
+    }
+
+    // 绘制两点之间的连线，不包含两个端点，端点一般由调用方绘制标记
+    // 只在空白处绘制，使用 - | / \ 以便 svgbob 识别为线段
+    pub fn line(&mut self, x0: usize, y0: usize, x1: usize, y1: usize) {
+        let (fx0, fy0) = (x0 as f64, y0 as f64);
+        let (dx, dy) = (x1 as f64 - fx0, y1 as f64 - fy0);
+        let slash = if dx * dy < 0.0 { '/' } else { '\\' };
+        if dx == 0.0 && dy == 0.0 {
+            return;
+        }
+        if dx.abs() >= dy.abs() {
+            let at = |x: f64| (fy0 + dy * (x - fx0) / dx).round();
+            let step: i64 = if dx > 0.0 { 1 } else { -1 };
+            let mut x = x0 as i64 + step;
+            while x != x1 as i64 {
+                let fx = x as f64;
+                let c = if at(fx - 0.5) == at(fx + 0.5) {
+                    '-'
+                } else {
+                    slash
+                };
+                self.put_blank(x as usize, at(fx) as usize, c);
+                x += step;
+            }
+        } else {
+            let at = |y: f64| (fx0 + dx * (y - fy0) / dy).round();
+            let step: i64 = if dy > 0.0 { 1 } else { -1 };
+            let mut y = y0 as i64 + step;
+            while y != y1 as i64 {
+                let fy = y as f64;
+                let c = if at(fy - 0.5) == at(fy + 0.5) {
+                    '|'
+                } else {
+                    slash
+                };
+                self.put_blank(at(fy) as usize, y as usize, c);
+                y += step;
+            }
+        }
+    }
+
+    // 绘制 svgbob 圆角矩形，(x, y) 为左上角
+    pub fn round_box(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        if w < 2 || h < 2 {
+            return;
+        }
+        self.hline(x + 1, x + w - 2, y, '-');
+        self.hline(x + 1, x + w - 2, y + h - 1, '-');
+        self.vline(x, y + 1, y + h - 2, '|');
+        self.vline(x + w - 1, y + 1, y + h - 2, '|');
+        self.put(x, y, '.');
+        self.put(x + w - 1, y, '.');
+        self.put(x, y + h - 1, '\'');
+        self.put(x + w - 1, y + h - 1, '\'');
+    }
+
+    pub fn render(&self) -> String {
+        let mut content = String::new();
+        for row in self.rows.iter() {
+            let line: String = row.iter().filter(|&&c| c != WIDE_HOLDER).collect();
+            content.push_str(line.trim_end());
+            content.push('\n');
+        }
+        // 去掉尾部空行
+        while content.ends_with("\n\n") {
+            content.pop();
+        }
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canvas() {
+        let mut canvas = ACanvas::new(4, 1);
+        canvas.text(0, 0, "你好a");
+        canvas.round_box(0, 1, 4, 3);
+        assert_eq!(canvas.w, 5);
+        assert_eq!(canvas.render(), "你好a\n.--.\n|  |\n'--'\n");
+    }
+}
@@ -1,3 +1,5 @@
 mod calurator;
+mod canvas;
 
-pub use calurator::cn_length;
+pub use calurator::{cn_length, escape};
+pub use canvas::ACanvas;
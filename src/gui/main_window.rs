@@ -53,6 +53,9 @@ mod imp {
             klass.install_action("win.clear_all", None, |window, _, _| {
                 window.clear_all();
             });
+            klass.install_action("win.open", None, |window, _, _| {
+                window.open();
+            });
             klass.install_action("win.switch_tab", None, |window, _, _| {
                 window.switch_tab();
             });
@@ -176,6 +179,13 @@ impl MainWindow {
         }
     }
 
+    // 打开的文件总是显示在 svgbob 页面
+    fn open(&self) {
+        let imp = self.imp();
+        imp.stack.set_visible_child_name("svgbob");
+        let _ = imp.svgbob.activate_action("svgbob.do_open", None);
+    }
+
     fn switch_tab(&self) {
         let mut names: Vec<&str> = Vec::new();
         names.push("svgbob");
@@ -9,3 +9,20 @@ pub use page_adoc::AdocPage;
 pub use page_svgbob::SvgbobPage;
 pub use preferences::MainPreferences;
 pub use theme_selector::ThemeSelector;
+
+use crate::core::config::AConfig;
+
+// 从启动目录向上查找 asciibox.toml，出错时使用默认配置
+pub fn project_config() -> AConfig {
+    let dir = match std::env::current_dir() {
+        Ok(v) => v,
+        Err(_) => return AConfig::default(),
+    };
+    match AConfig::load_from(dir.as_path()) {
+        Ok(v) => v,
+        Err(error) => {
+            println!("Error load config: {error}");
+            AConfig::default()
+        }
+    }
+}
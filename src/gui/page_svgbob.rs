@@ -8,9 +8,12 @@ use gtk::prelude::{TextBufferExt, TextViewExt};
 use gtk::CompositeTemplate;
 use std::fs::OpenOptions;
 use std::io::Write;
-use svgbob::to_svg;
 
-use crate::core::svgbob::AMap;
+use crate::core::diagram::{self, DiagramOptions};
+use crate::core::export::{
+    ascii_to_svg, embed_source, extract_source, parse_color, svg_to_pdf, svg_to_png, to_html,
+    AEmbeddedSource, AHtmlOptions, APngOptions, ASvgOptions, ASvgTheme,
+};
 
 mod imp {
 
@@ -29,6 +32,10 @@ mod imp {
         pub out_image: TemplateChild<gtk::Image>,
 
         pub icon_str_backup: RefCell<String>,
+        // 从 svg 中恢复的渲染选项，清空输入前代替设置使用
+        pub restored_options: RefCell<Option<DiagramOptions>>,
+        // (ascii 图, 带节点样式标签的 ascii 图)，ascii 图未被修改时用后者生成 svg
+        pub tagged_ascii: RefCell<(String, String)>,
         pub settings: OnceCell<Settings>,
     }
 
@@ -64,6 +71,16 @@ mod imp {
                 },
             );
 
+            klass.install_action_async(
+                "svgbob.do_open",
+                None,
+                |win, _action_name, _action_target| async move {
+                    if let Err(error) = win.do_open_file().await {
+                        println!("Error Open file: {error}");
+                    };
+                },
+            );
+
             klass.install_action("svgbob.do_transform_copy", None, move |obj, _, _| {
                 obj.do_transform_copy();
             });
@@ -85,6 +102,7 @@ mod imp {
             let obj = self.obj();
             obj.setup_text_view();
             obj.setup_settings();
+            obj.setup_drop_target();
         }
     }
     impl WidgetImpl for SvgbobPage {}
@@ -135,6 +153,13 @@ impl SvgbobPage {
             .settings
             .set(settings)
             .expect("Could not set `Settings`.");
+
+        // 切换深色模式时重新生成预览
+        adw::StyleManager::default().connect_dark_notify(
+            glib::clone!(@weak self as page => move |_| {
+                page.do_transform_to_svg();
+            }),
+        );
     }
 
     fn do_transform(&self) {
@@ -143,10 +168,15 @@ impl SvgbobPage {
 
         // 当输入为 0 的时候不覆盖，这样可以编辑 svgbob 窗口并转换
         if content.len() != 0 {
-            let settings = self.imp().settings.get().expect("could not get settings");
-            let expand_mode = settings.boolean("expand-mode");
-            let mut mmap: AMap = AMap::new(expand_mode);
-            let otext: String = mmap.load_content(content.as_str());
+            // 依据首行自动识别图表类型，默认为流程图
+            let options = self.diagram_options();
+            let otext: String = diagram::render(content.as_str(), &options);
+            let tagged = DiagramOptions {
+                css_tags: true,
+                ..options
+            };
+            let tagged = diagram::render(content.as_str(), &tagged);
+            self.imp().tagged_ascii.replace((otext.clone(), tagged));
 
             let obuffer = self.imp().out_view.get().buffer();
             obuffer.set_text(otext.as_str());
@@ -155,9 +185,99 @@ impl SvgbobPage {
         self.do_transform_to_svg();
     }
 
+    // 项目配置 asciibox.toml 优先，界面中的扩展开关同样生效
+    fn diagram_options(&self) -> DiagramOptions {
+        if let Some(options) = self.imp().restored_options.borrow().clone() {
+            return options;
+        }
+        let settings = self.imp().settings.get().expect("could not get settings");
+        let mut options = super::project_config().diagram_options();
+        options.expand_mode |= settings.boolean("expand-mode");
+        options
+    }
+
     fn do_clear(&self) {
         let ibuffer: gtk::TextBuffer = self.imp().in_view.get().buffer();
         ibuffer.set_text("");
+        self.imp().restored_options.replace(None);
+    }
+
+    // 拖入文件时与打开文件相同
+    fn setup_drop_target(&self) {
+        let target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+        target.set_propagation_phase(gtk::PropagationPhase::Capture);
+        target.connect_drop(
+            glib::clone!(@weak self as page => @default-return false, move |_, value, _, _| {
+                let file = match value.get::<gdk::FileList>() {
+                    Ok(files) => files.files().into_iter().next(),
+                    Err(_) => None,
+                };
+                match file.map(|file| page.load_file(&file)) {
+                    Some(Ok(())) => true,
+                    Some(Err(error)) => {
+                        println!("Error Open file: {error}");
+                        false
+                    }
+                    None => false,
+                }
+            }),
+        );
+        self.add_controller(target);
+    }
+
+    pub async fn do_open_file(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        for (name, patterns) in [
+            (
+                "Diagram",
+                vec!["*.svg", "*.mmd", "*.mermaid", "*.dot", "*.gv", "*.txt"],
+            ),
+            ("SVG", vec!["*.svg"]),
+            ("All Files", vec!["*"]),
+        ] {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(name));
+            for pattern in patterns {
+                filter.add_pattern(pattern);
+            }
+            filters.append(&filter);
+        }
+        let dialog = gtk::FileDialog::builder()
+            .title("Open Diagram")
+            .accept_label("Open")
+            .filters(&filters)
+            .modal(true)
+            .build();
+
+        let window = self.root().and_downcast::<gtk::Window>().unwrap();
+        let file: gio::File = dialog.open_future(Some(&window)).await?;
+        self.load_file(&file)
+    }
+
+    // asciibox 保存的 svg 恢复输入和 ascii 图，其他文本文件作为输入内容
+    fn load_file(&self, file: &gio::File) -> Result<(), Box<dyn std::error::Error>> {
+        let (bytes, _) = file.load_contents(gio::Cancellable::NONE)?;
+        let content = String::from_utf8(bytes.to_vec())?;
+        let imp = self.imp();
+        if content.trim_start().starts_with('<') {
+            let source = extract_source(content.as_str())
+                .ok_or("the svg was not saved by asciibox, no source to restore")?;
+            imp.in_view.get().buffer().set_text(source.source.as_str());
+            imp.restored_options.replace(Some(source.diagram_options()));
+            // 保存时的 ascii 图与重新生成的相同时重新生成，保留节点的颜色
+            let options = source.diagram_options();
+            if diagram::render(source.source.as_str(), &options) == source.ascii {
+                self.do_transform();
+                return Ok(());
+            }
+            imp.out_view.get().buffer().set_text(source.ascii.as_str());
+            self.do_transform_to_svg();
+            return Ok(());
+        }
+        imp.restored_options.replace(None);
+        imp.in_view.get().buffer().set_text(content.as_str());
+        self.do_transform();
+        Ok(())
     }
 
     fn do_copy_svg_file(&self) {
@@ -165,25 +285,133 @@ impl SvgbobPage {
         clipboard.set_text(self.imp().icon_str_backup.borrow().as_str());
     }
 
+    // 根据扩展名保存为 svg、png、pdf 或 html，没有扩展名时保存为 svg
     pub async fn do_save_svg_file(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        for (name, pattern) in [
+            ("SVG", "*.svg"),
+            ("PNG", "*.png"),
+            ("PDF", "*.pdf"),
+            ("HTML", "*.html"),
+        ] {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(name));
+            filter.add_pattern(pattern);
+            filters.append(&filter);
+        }
         let dialog = gtk::FileDialog::builder()
-            .title("Open File")
+            .title("Save Image")
             .accept_label("Save")
+            .initial_name("diagram.svg")
+            .filters(&filters)
             .modal(true)
             .build();
 
         let window = self.root().and_downcast::<gtk::Window>().unwrap();
         let file: gio::File = dialog.save_future(Some(&window)).await?;
         let mut filename = file.path().expect("Couldn't get file path");
-        if !filename.ends_with("svg") {
-            filename.set_extension("svg");
-        }
-        let mut file2: std::fs::File =
-            OpenOptions::new().write(true).create(true).open(filename)?;
-        file2.write_all(self.imp().icon_str_backup.borrow().as_bytes())?;
+        let extension = filename
+            .extension()
+            .map(|v| v.to_string_lossy().to_lowercase());
+        let svg = self.imp().icon_str_backup.borrow().clone();
+        let content = match extension.as_deref() {
+            Some("png") => svg_to_png(svg.as_str(), &self.png_options())?,
+            // 页面大小和页边距使用项目配置
+            Some("pdf") => svg_to_pdf(svg.as_str(), &super::project_config().pdf_options())?,
+            // 与预览相同的 svg，ascii 图和输入内容使用输出框和输入框中的文字
+            Some("html") | Some("htm") => {
+                let source = self.embedded_source();
+                let title = filename
+                    .file_stem()
+                    .map(|v| v.to_string_lossy().to_string());
+                let options = AHtmlOptions {
+                    title: title.unwrap_or(AHtmlOptions::default().title),
+                    ..AHtmlOptions::default()
+                };
+                to_html(
+                    svg.as_str(),
+                    source.ascii.as_str(),
+                    source.source.as_str(),
+                    &options,
+                )
+                .into_bytes()
+            }
+            _ => {
+                if !filename.ends_with("svg") {
+                    filename.set_extension("svg");
+                }
+                // 嵌入原始输入，再次打开时可以继续编辑
+                embed_source(svg.as_str(), &self.embedded_source()).into_bytes()
+            }
+        };
+        let mut file2: std::fs::File = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(filename)?;
+        file2.write_all(content.as_slice())?;
         Ok(())
     }
 
+    fn embedded_source(&self) -> AEmbeddedSource {
+        let text = |view: &gtk::TextView| {
+            let buffer = view.buffer();
+            buffer
+                .text(&buffer.bounds().0, &buffer.bounds().1, false)
+                .to_string()
+        };
+        AEmbeddedSource::new(
+            text(&self.imp().in_view.get()).as_str(),
+            text(&self.imp().out_view.get()).as_str(),
+            &self.diagram_options(),
+        )
+    }
+
+    // 项目配置 asciibox.toml 优先，否则使用首选项中的样式
+    fn svg_options(&self) -> ASvgOptions {
+        let config = super::project_config();
+        if config.path.is_some() {
+            return config.svg_options();
+        }
+        let settings = self.imp().settings.get().expect("could not get settings");
+        let mut options = ASvgOptions {
+            font_size: settings.double("svg-font-size").round() as usize,
+            stroke_width: settings.double("svg-stroke-width") as f32,
+            scale: settings.double("svg-scale") as f32,
+            transparent: settings.boolean("svg-transparent"),
+            theme: ASvgTheme::from_name(settings.string("svg-theme").as_str()).unwrap_or_default(),
+            ..ASvgOptions::default()
+        };
+        // 清空的文本框使用默认值
+        for (key, value) in [
+            ("svg-font-family", &mut options.font_family),
+            ("svg-stroke-color", &mut options.stroke_color),
+            ("svg-fill-color", &mut options.fill_color),
+            ("svg-background", &mut options.background),
+        ] {
+            let text = settings.string(key);
+            if !text.trim().is_empty() {
+                *value = text.trim().to_string();
+            }
+        }
+        options
+    }
+
+    // 项目配置 asciibox.toml 优先，否则使用首选项中的缩放和背景
+    fn png_options(&self) -> APngOptions {
+        let config = super::project_config();
+        if config.path.is_some() {
+            return config.png_options();
+        }
+        let settings = self.imp().settings.get().expect("could not get settings");
+        let background = settings.string("png-background");
+        APngOptions {
+            scale: settings.double("png-scale") as f32,
+            // 为空或者无法识别时保留白色背景
+            background: parse_color(background.as_str()).map(|_| background.to_string()),
+        }
+    }
+
     fn do_transform_copy(&self) {
         let clipboard = self.clipboard();
         let buffer = self.imp().out_view.get().buffer();
@@ -191,13 +419,36 @@ impl SvgbobPage {
         clipboard.set_text(content.as_str());
     }
 
+    // 预览跟随应用的深色模式，复制和保存使用设置中的主题
     fn do_transform_to_svg(&self) {
         let buffer = self.imp().out_view.get().buffer();
-        let content = buffer.text(&buffer.bounds().0, &buffer.bounds().1, false);
-        let svg_content = to_svg(content.as_str());
+        let mut content = buffer
+            .text(&buffer.bounds().0, &buffer.bounds().1, false)
+            .to_string();
+        // 节点的颜色只出现在 svg 中，手工修改过的 ascii 图没有颜色
+        let (plain, tagged) = self.imp().tagged_ascii.borrow().clone();
+        if content == plain {
+            content = tagged;
+        }
+        let options = self.svg_options();
+        let svg_content = ascii_to_svg(content.as_str(), &options);
+        let preview_theme = match adw::StyleManager::default().is_dark() {
+            true => ASvgTheme::Dark,
+            false => ASvgTheme::Light,
+        };
+        let preview = match options.theme == preview_theme {
+            true => svg_content.clone(),
+            false => ascii_to_svg(
+                content.as_str(),
+                &ASvgOptions {
+                    theme: preview_theme,
+                    ..options
+                },
+            ),
+        };
 
         let texture: gdk::Texture =
-            gdk::Texture::from_bytes(&glib::Bytes::from(svg_content.as_bytes()))
+            gdk::Texture::from_bytes(&glib::Bytes::from(preview.as_bytes()))
                 .expect("load svgbob out svg error");
         self.imp()
             .out_image
@@ -6,6 +6,8 @@ use gtk::glib::clone;
 use gtk::{glib, prelude::*, subclass::prelude::*, CompositeTemplate, *};
 use std::cell::OnceCell;
 
+const SVG_THEMES: [&str; 3] = ["light", "dark", "auto"];
+
 mod imp {
 
     use std::sync::OnceLock;
@@ -24,6 +26,28 @@ mod imp {
         pub font: TemplateChild<FontDialogButton>,
         #[template_child]
         pub expand_mode: TemplateChild<Switch>,
+        #[template_child]
+        pub svg_font_family: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub svg_font_size: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub svg_stroke_width: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub svg_scale: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub svg_stroke_color: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub svg_fill_color: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub svg_background: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub svg_transparent: TemplateChild<Switch>,
+        #[template_child]
+        pub svg_theme: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub png_scale: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub png_background: TemplateChild<adw::EntryRow>,
     }
 
     #[glib::object_subclass]
@@ -118,6 +142,69 @@ impl MainPreferences {
             .bind("expand-mode", &expand_mode, "active")
             .flags(SettingsBindFlags::DEFAULT)
             .build();
+
+        // svgbob 生成 svg 时的样式
+        let imp = self.imp();
+        let svg_rows: [(&str, glib::Object, &str); 8] = [
+            (
+                "svg-font-family",
+                imp.svg_font_family.get().upcast(),
+                "text",
+            ),
+            ("svg-font-size", imp.svg_font_size.get().upcast(), "value"),
+            (
+                "svg-stroke-width",
+                imp.svg_stroke_width.get().upcast(),
+                "value",
+            ),
+            ("svg-scale", imp.svg_scale.get().upcast(), "value"),
+            (
+                "svg-stroke-color",
+                imp.svg_stroke_color.get().upcast(),
+                "text",
+            ),
+            ("svg-fill-color", imp.svg_fill_color.get().upcast(), "text"),
+            ("svg-background", imp.svg_background.get().upcast(), "text"),
+            (
+                "svg-transparent",
+                imp.svg_transparent.get().upcast(),
+                "active",
+            ),
+        ];
+        for (key, object, property) in svg_rows {
+            self.settings()
+                .bind(key, &object, property)
+                .flags(SettingsBindFlags::DEFAULT)
+                .build();
+        }
+
+        // 下拉框的序号与 svg-theme 的取值一一对应
+        let svg_theme = self.imp().svg_theme.get();
+        self.settings()
+            .bind("svg-theme", &svg_theme, "selected")
+            .mapping(|theme, _| {
+                let theme = theme.get::<String>()?;
+                let index = SVG_THEMES.iter().position(|v| *v == theme.as_str())?;
+                Some((index as u32).to_value())
+            })
+            .set_mapping(|value, _| {
+                let index = value.get::<u32>().ok()? as usize;
+                SVG_THEMES.get(index).map(|v| v.to_variant())
+            })
+            .flags(SettingsBindFlags::DEFAULT)
+            .build();
+
+        let png_scale = self.imp().png_scale.get();
+        self.settings()
+            .bind("png-scale", &png_scale, "value")
+            .flags(SettingsBindFlags::DEFAULT)
+            .build();
+
+        let png_background = self.imp().png_background.get();
+        self.settings()
+            .bind("png-background", &png_background, "text")
+            .flags(SettingsBindFlags::DEFAULT)
+            .build();
     }
 
     pub(crate) fn connect_font_changed<F: Fn(&Self) + 'static>(
@@ -0,0 +1,7 @@
+// 核心库，不依赖 gtk，可以被其他工具直接使用
+// 使用 default-features = false 关闭图形界面、命令行和 png/pdf 导出
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod core;
+#[cfg(feature = "lsp")]
+pub mod lsp;
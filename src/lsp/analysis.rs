@@ -0,0 +1,258 @@
+use lsp_types::{
+    CodeAction, CodeActionKind, Diagnostic, DiagnosticSeverity, Hover, HoverContents,
+    MarkupContent, MarkupKind, Position, Range, TextEdit, Uri, WorkspaceEdit,
+};
+use std::collections::HashMap;
+
+use crate::core::config::AConfig;
+use crate::core::diagram;
+use crate::core::document::{find_blocks, ADocFormat, ADocProcessor};
+
+// 编辑器中打开的文件类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AFileKind {
+    // 整个文件为一个 mermaid 图
+    Mermaid,
+    // 包含 mermaid 块的文档
+    Document(ADocFormat),
+}
+
+impl AFileKind {
+    pub fn detect(uri: &str, language_id: &str) -> Self {
+        let ext = uri.rsplit('.').next().unwrap_or("").to_lowercase();
+        if language_id == "mermaid" || ext == "mmd" || ext == "mermaid" {
+            return AFileKind::Mermaid;
+        }
+        AFileKind::Document(ADocFormat::from_path(uri))
+    }
+}
+
+// 文件中的一个 mermaid 图，行号范围 [start, end] 包含分隔行
+#[derive(Debug, Clone, PartialEq)]
+struct ADiagramRange {
+    start: usize,
+    end: usize,
+    body_start: usize,
+    body: String,
+}
+
+fn diagram_ranges(text: &str, kind: AFileKind) -> Vec<ADiagramRange> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let format = match kind {
+        AFileKind::Mermaid => {
+            return vec![ADiagramRange {
+                start: 0,
+                end: lines.len() - 1,
+                body_start: 0,
+                body: text.to_string(),
+            }];
+        }
+        AFileKind::Document(v) => v,
+    };
+    find_blocks(&lines, format)
+        .into_iter()
+        .filter(|b| b.lang == "mermaid")
+        .map(|b| ADiagramRange {
+            start: b.start,
+            end: b.end,
+            body_start: b.body_start,
+            body: b.body,
+        })
+        .collect()
+}
+
+// lsp 中的列以 utf-16 为单位
+fn utf16_len(line: &str) -> u32 {
+    line.encode_utf16().count() as u32
+}
+
+fn position(line: usize, character: u32) -> Position {
+    Position::new(line as u32, character)
+}
+
+pub fn diagnostics(text: &str, kind: AFileKind) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut result: Vec<Diagnostic> = Vec::new();
+    for range in diagram_ranges(text, kind) {
+        for d in diagram::diagnose(range.body.as_str()) {
+            let line = range.body_start + d.line;
+            let width = lines.get(line).map(|l| utf16_len(l)).unwrap_or(0);
+            result.push(Diagnostic {
+                range: Range::new(position(line, 0), position(line, width)),
+                severity: Some(DiagnosticSeverity::WARNING),
+                source: Some("asciibox".to_string()),
+                message: d.message,
+                ..Default::default()
+            });
+        }
+    }
+    result
+}
+
+pub fn hover(text: &str, kind: AFileKind, config: &AConfig, pos: Position) -> Option<Hover> {
+    let line = pos.line as usize;
+    let range = diagram_ranges(text, kind)
+        .into_iter()
+        .find(|r| r.start <= line && line <= r.end)?;
+    let ascii = diagram::render(range.body.as_str(), &config.diagram_options());
+    if ascii.trim().is_empty() {
+        return None;
+    }
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("```text\n{}```", ascii),
+        }),
+        range: None,
+    })
+}
+
+// 按行替换 [start, end)，处理文件末尾没有换行的情况
+fn line_edit(lines: &[&str], start: usize, end: usize, new_lines: &[String]) -> TextEdit {
+    if end < lines.len() {
+        let text: String = new_lines.iter().map(|l| format!("{}\n", l)).collect();
+        return TextEdit::new(Range::new(position(start, 0), position(end, 0)), text);
+    }
+    let last = lines.len() - 1;
+    let eof = position(last, utf16_len(lines[last]));
+    if start < lines.len() {
+        let range = Range::new(position(start, 0), eof);
+        return TextEdit::new(range, new_lines.join("\n"));
+    }
+    TextEdit::new(Range::new(eof, eof), format!("\n{}", new_lines.join("\n")))
+}
+
+// 光标所在 mermaid 块的插入/刷新 svgbob 操作
+// WorkspaceEdit 要求以 Uri 为键，其内部缓存不影响哈希
+#[allow(clippy::mutable_key_type)]
+pub fn code_actions(
+    text: &str,
+    kind: AFileKind,
+    config: &AConfig,
+    uri: &Uri,
+    range: Range,
+) -> Vec<CodeAction> {
+    let format = match kind {
+        AFileKind::Document(v) => v,
+        AFileKind::Mermaid => return Vec::new(),
+    };
+    let line = range.start.line as usize;
+    let target = match diagram_ranges(text, kind)
+        .into_iter()
+        .find(|r| r.start <= line && line <= r.end)
+    {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut processor = ADocProcessor::new(format);
+    processor.options = config.diagram_options();
+    let edit = match processor
+        .diagram_edits(text)
+        .into_iter()
+        .find(|e| e.block == target.start)
+    {
+        Some(v) => v,
+        None => return Vec::new(),
+    };
+    let old: Vec<String> = lines[edit.start..edit.end]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+    if old == edit.lines {
+        return Vec::new();
+    }
+    let title = match edit.start == edit.end {
+        true => "Insert svgbob rendering",
+        false => "Refresh svgbob rendering",
+    };
+    let mut changes = HashMap::new();
+    changes.insert(
+        uri.clone(),
+        vec![line_edit(&lines, edit.start, edit.end, &edit.lines)],
+    );
+    vec![CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(WorkspaceEdit::new(changes)),
+        ..Default::default()
+    }]
+}
+
+// asciidoc 表格格式化，整个文件作为一个修改
+pub fn formatting(text: &str, kind: AFileKind, config: &AConfig) -> Vec<TextEdit> {
+    let mut processor = match kind {
+        AFileKind::Document(ADocFormat::Asciidoc) => ADocProcessor::new(ADocFormat::Asciidoc),
+        _ => return Vec::new(),
+    };
+    processor.table_width = config.table.max_width;
+    let result = processor.process_tables(text);
+    if result == text {
+        return Vec::new();
+    }
+    let lines: Vec<&str> = text.split('\n').collect();
+    let last = lines.len() - 1;
+    let range = Range::new(position(0, 0), position(last, utf16_len(lines[last])));
+    vec![TextEdit::new(range, result)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const DOC: &str = "# t\n\n```mermaid\npie\n\"a\" : x\n```\n\n```mermaid\na --> b\n```";
+
+    #[test]
+    fn test_diagnostics_and_hover() {
+        let config = AConfig::default();
+        let kind = AFileKind::detect("file:///a/readme.md", "markdown");
+        let result = diagnostics(DOC, kind);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].range.start.line, 4);
+        assert_eq!(result[0].range.end.character, 7);
+        let result = diagnostics("a --> b\nlinkStyle 0 stroke:red\n", AFileKind::Mermaid);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].range.start.line, 1);
+        assert!(result[0].message.contains("linkStyle is not supported"));
+
+        assert!(hover(DOC, kind, &config, Position::new(0, 0)).is_none());
+        let value = match hover(DOC, kind, &config, Position::new(8, 1)).map(|h| h.contents) {
+            Some(HoverContents::Markup(v)) => v.value,
+            v => panic!("unexpected {:?}", v),
+        };
+        assert!(value.contains("| a |-->| b |"));
+
+        let kind = AFileKind::detect("file:///a/flow.mmd", "");
+        assert_eq!(kind, AFileKind::Mermaid);
+        assert!(hover("a --> b\n", kind, &config, Position::new(0, 0)).is_some());
+    }
+
+    #[test]
+    fn test_code_actions() {
+        let config = AConfig::default();
+        let uri = Uri::from_str("file:///a/readme.md").unwrap();
+        let kind = AFileKind::detect(uri.as_str(), "markdown");
+        let range = Range::new(Position::new(8, 0), Position::new(8, 0));
+        let actions = code_actions(DOC, kind, &config, &uri, range);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Insert svgbob rendering");
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range.start, Position::new(9, 3));
+        assert!(edits[0].new_text.starts_with("\n\n```svgbob\n.---."));
+
+        // 已经是最新的结果时不再提供操作
+        let mut processor = ADocProcessor::new(ADocFormat::Markdown);
+        let text = processor.do_process(DOC);
+        assert!(code_actions(text.as_str(), kind, &config, &uri, range).is_empty());
+    }
+
+    #[test]
+    fn test_formatting() {
+        let config = AConfig::default();
+        let kind = AFileKind::detect("file:///a/t.adoc", "asciidoc");
+        let edits = formatting("|===\n|a|b\n|===\n", kind, &config);
+        assert_eq!(edits[0].new_text, "|======\n| a | b\n|======\n");
+        assert!(formatting("|===\n|a|b\n|===\n", AFileKind::Mermaid, &config).is_empty());
+    }
+}
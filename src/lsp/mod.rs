@@ -0,0 +1,201 @@
+mod analysis;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, Formatting, HoverRequest, Request as LspRequest};
+use lsp_types::{
+    CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability,
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentFormattingParams, HoverParams, HoverProviderCapability, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Uri,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::core::config::AConfig;
+
+pub use analysis::AFileKind;
+
+// 编辑器中打开的文档
+struct ADocument {
+    kind: AFileKind,
+    text: String,
+}
+
+// 基于 stdio 的语言服务，提供诊断、悬停预览、代码操作及 asciidoc 表格格式化
+pub struct AServer {
+    connection: Connection,
+    documents: HashMap<String, ADocument>,
+    config: AConfig,
+}
+
+fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+impl AServer {
+    pub fn new(connection: Connection, config: AConfig) -> Self {
+        Self {
+            connection,
+            documents: HashMap::new(),
+            config,
+        }
+    }
+
+    pub fn do_run(&mut self) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let capabilities = serde_json::to_value(capabilities())?;
+        self.connection.initialize(capabilities)?;
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.connection.sender.send(Message::Response(response))?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => self.hover(request.params),
+            CodeActionRequest::METHOD => self.code_action(request.params),
+            Formatting::METHOD => self.formatting(request.params),
+            _ => {
+                let message = format!("unknown method '{}'", request.method);
+                return Response::new_err(id, ErrorCode::MethodNotFound as i32, message);
+            }
+        };
+        match result {
+            Ok(v) => Response::new_ok(id, v),
+            Err(e) => Response::new_err(id, ErrorCode::InvalidParams as i32, e.to_string()),
+        }
+    }
+
+    fn document(&self, uri: &Uri) -> Option<&ADocument> {
+        self.documents.get(uri.as_str())
+    }
+
+    fn hover(&self, params: Value) -> Result<Value, serde_json::Error> {
+        let params: HoverParams = serde_json::from_value(params)?;
+        let position = params.text_document_position_params;
+        let result = self.document(&position.text_document.uri).and_then(|d| {
+            analysis::hover(d.text.as_str(), d.kind, &self.config, position.position)
+        });
+        serde_json::to_value(result)
+    }
+
+    fn code_action(&self, params: Value) -> Result<Value, serde_json::Error> {
+        let params: CodeActionParams = serde_json::from_value(params)?;
+        let uri = &params.text_document.uri;
+        let result: Vec<CodeActionOrCommand> = match self.document(uri) {
+            Some(d) => {
+                analysis::code_actions(d.text.as_str(), d.kind, &self.config, uri, params.range)
+                    .into_iter()
+                    .map(CodeActionOrCommand::CodeAction)
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        serde_json::to_value(result)
+    }
+
+    fn formatting(&self, params: Value) -> Result<Value, serde_json::Error> {
+        let params: DocumentFormattingParams = serde_json::from_value(params)?;
+        let result = self
+            .document(&params.text_document.uri)
+            .map(|d| analysis::formatting(d.text.as_str(), d.kind, &self.config));
+        serde_json::to_value(result)
+    }
+
+    fn handle_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let item = params.text_document;
+                let kind = AFileKind::detect(item.uri.as_str(), item.language_id.as_str());
+                self.documents.insert(
+                    item.uri.as_str().to_string(),
+                    ADocument {
+                        kind,
+                        text: item.text,
+                    },
+                );
+                self.publish(item.uri)?;
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                // 使用全量同步，最后一个修改即为完整内容
+                if let (Some(document), Some(change)) = (
+                    self.documents.get_mut(uri.as_str()),
+                    params.content_changes.into_iter().last(),
+                ) {
+                    document.text = change.text;
+                }
+                self.publish(uri)?;
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents.remove(uri.as_str());
+                // 关闭后清空诊断
+                self.send_diagnostics(uri, Vec::new())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn publish(&self, uri: Uri) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let diagnostics = match self.document(&uri) {
+            Some(d) => analysis::diagnostics(d.text.as_str(), d.kind),
+            None => return Ok(()),
+        };
+        self.send_diagnostics(uri, diagnostics)
+    }
+
+    fn send_diagnostics(
+        &self,
+        uri: Uri,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection
+            .sender
+            .send(Message::Notification(notification))?;
+        Ok(())
+    }
+}
+
+// 在标准输入输出上运行语言服务，直到客户端发送 shutdown/exit
+pub fn run_stdio(config: AConfig) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+    AServer::new(connection, config).do_run()?;
+    io_threads.join()?;
+    Ok(())
+}
@@ -1,14 +1,31 @@
+#[cfg(feature = "gui")]
 mod application;
+#[cfg(feature = "gui")]
 mod config;
-mod core;
+#[cfg(feature = "gui")]
 mod gui;
+
+use asciibox::cli;
+#[cfg(feature = "gui")]
+use asciibox::core; // gui 中通过 crate::core 引用核心库
+
+#[cfg(feature = "gui")]
 use application::AsciiboxApplication;
+#[cfg(feature = "gui")]
+use config::{APP_ID, PKGDATA_DIR};
+#[cfg(feature = "gui")]
 use gtk::prelude::*;
+#[cfg(feature = "gui")]
 use gtk::{gio, glib};
 
-use config::{APP_ID, PKGDATA_DIR};
-
+#[cfg(feature = "gui")]
 fn main() -> glib::ExitCode {
+    // 带子命令时以命令行方式运行，不需要显示器和资源文件
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if cli::is_cli(&args) {
+        std::process::exit(cli::run(&args));
+    }
+
     // Register and include resources
     let resources = gio::Resource::load(PKGDATA_DIR.to_owned() + "/asciibox.gresource")
         .expect("Could not load resources");
@@ -24,9 +41,18 @@ fn main() -> glib::ExitCode {
     app.run()
 }
 
+// 未启用图形界面时只有命令行模式
+#[cfg(not(feature = "gui"))]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(cli::run(&args));
+}
+
+#[cfg(feature = "gui")]
 fn setup_shortcuts(app: &AsciiboxApplication) {
     app.set_accels_for_action("app.quit", &["<Ctrl>q"]);
     app.set_accels_for_action("win.execute", &["<Ctrl>r"]);
+    app.set_accels_for_action("win.open", &["<Ctrl>o"]);
     app.set_accels_for_action("win.clear_all", &["<Ctrl>BackSpace"]);
     app.set_accels_for_action("win.switch_tab", &["<Ctrl>h"]);
 }